@@ -0,0 +1,135 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use colored::Colorize;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::changelog;
+use crate::git;
+use crate::llms::get_llm_client;
+use crate::output::{MessageOutput, OutputFormat};
+use crate::settings::Settings;
+use crate::summarize::{SummarizationClient, SummarizationReport};
+use crate::util::format_date;
+
+/// Arguments for the Changelog action
+#[derive(Args, Debug)]
+pub(crate) struct ChangelogArgs {
+    /// Start of the commit range, excluded. Defaults to the latest tag before --to
+    #[arg(long)]
+    from: Option<String>,
+
+    /// End of the commit range, included
+    #[arg(long, default_value = "HEAD")]
+    to: String,
+
+    /// Name of the release, such as 1.2.0
+    #[arg(long, default_value = "Unreleased")]
+    release: String,
+
+    /// Add a summary written by the model to each section, overriding
+    /// `changelog.summarize`
+    #[arg(long)]
+    summarize: bool,
+
+    /// Write the release notes to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// How to print the release notes
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// Print the prompts that would be sent to the model without sending them,
+    /// and don't write the release notes
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub(crate) async fn main(mut settings: Settings, args: ChangelogArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let started = Instant::now();
+    let git_client = git::get_git_client(&settings);
+    let from = match args.from {
+        Some(from) => from,
+        None => {
+            git_client.resolve_commit(&args.to)?;
+            // look before the tip, in case the tip is the tag of the release itself;
+            // a root commit has no parent and no earlier tag
+            let latest_tag = match git_client.resolve_commit(&format!("{}^", args.to)) {
+                Ok(parent) => git_client.get_latest_tag(&parent)?,
+                Err(_) => None,
+            };
+            latest_tag.ok_or(anyhow!(
+                "No tag found before {}. Pass the start of the range with --from.",
+                args.to
+            ))?
+        }
+    };
+    debug!("Writing release notes for {from}..{}", args.to);
+
+    let commits = git_client.get_commits(&from, &args.to)?;
+    if commits.is_empty() {
+        bail!("There are no commits between {from} and {}.", args.to);
+    }
+    let mut sections = changelog::group_commits(&commits);
+
+    let changelog_settings = settings.changelog.clone().unwrap_or_default();
+    // without summaries the model is never asked and the report stays empty
+    let mut report = SummarizationReport::default();
+    if args.summarize || changelog_settings.summarize.unwrap_or(false) {
+        let client = get_llm_client(&settings);
+        let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+        eprintln!(
+            "{}",
+            "🤖 Let's ask OpenAI to summarize those commits! 🚀"
+                .green()
+                .bold()
+        );
+        for section in sections.iter_mut() {
+            let commit_subjects = section
+                .entries
+                .iter()
+                .map(|entry| format!("- {}", entry.subject))
+                .collect::<Vec<String>>()
+                .join("\n");
+            section.summary = summarization_client
+                .changelog_summary(&section.name, &commit_subjects)
+                .await?;
+        }
+        report = summarization_client.report();
+    }
+    if settings.dry_run == Some(true) {
+        return Ok(());
+    }
+
+    // the release date is today in UTC
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let release_notes = changelog::render(
+        &changelog_settings.template.unwrap_or_default(),
+        &args.release,
+        &format_date(now),
+        &sections,
+    )?;
+    let release_notes = match args.format {
+        OutputFormat::Text => release_notes,
+        OutputFormat::Json => format!(
+            "{}\n",
+            MessageOutput::new(&release_notes, report, started.elapsed()).to_json()
+        ),
+    };
+
+    match args.output {
+        Some(path) => {
+            fs::write(&path, release_notes)?;
+            eprintln!("Wrote the release notes to {}", path.display());
+        }
+        None => print!("{release_notes}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use colored::Colorize;
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::actions::prepare_commit_msg::strip_comments;
+use crate::cmd::{run_command, run_command_interactive, run_command_interactive_with_env};
+use crate::diff;
+use crate::git::{self, DiffBase, GitClient};
+use crate::llms::get_llm_client;
+use crate::settings::Settings;
+use crate::summarize::SummarizationClient;
+
+/// Arguments for the Commit action
+#[derive(Args, Debug)]
+pub(crate) struct CommitArgs {
+    /// Commit with the generated message without asking
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Number of candidate messages to choose from, overriding `output.candidates`
+    #[arg(short, long)]
+    candidates: Option<u32>,
+
+    /// Print the prompts that would be sent to the model without sending them
+    /// or committing
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Extra arguments passed to `git commit`, such as --no-verify or --signoff
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    git_args: Vec<String>,
+}
+
+/// What the user wants to do with the generated commit message
+enum Choice {
+    Accept,
+    Edit,
+    Regenerate,
+    Abort,
+}
+
+/// Asks the user what to do with the commit message. Reaching the end of stdin
+/// aborts the commit.
+fn ask_choice() -> Result<Choice> {
+    let stdin = io::stdin();
+    loop {
+        print!(
+            "{} ",
+            "Commit with this message? [y]es, [e]dit, [r]egenerate, [a]bort:".bold()
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            println!();
+            return Ok(Choice::Abort);
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(Choice::Accept),
+            "e" | "edit" => return Ok(Choice::Edit),
+            "r" | "regenerate" => return Ok(Choice::Regenerate),
+            "a" | "abort" | "q" | "quit" => return Ok(Choice::Abort),
+            _ => println!("Please answer y, e, r or a."),
+        }
+    }
+}
+
+/// Lists the candidate messages and asks the user to pick one. Returns `None`
+/// when the user aborts.
+fn pick_candidate(candidates: &[String]) -> Result<Option<usize>> {
+    for (idx, candidate) in candidates.iter().enumerate() {
+        println!(
+            "{}\n{}\n",
+            format!("[{}]", idx + 1).bold(),
+            candidate.trim()
+        );
+    }
+    let stdin = io::stdin();
+    loop {
+        print!(
+            "{} ",
+            format!("Pick a message [1-{}] or [a]bort:", candidates.len()).bold()
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            println!();
+            return Ok(None);
+        }
+        let answer = answer.trim().to_lowercase();
+        if matches!(answer.as_str(), "a" | "abort" | "q" | "quit") {
+            return Ok(None);
+        }
+        match answer.parse::<usize>() {
+            Ok(choice) if (1..=candidates.len()).contains(&choice) => return Ok(Some(choice - 1)),
+            _ => println!("Please answer a number between 1 and {}.", candidates.len()),
+        }
+    }
+}
+
+/// Opens the commit message in the editor git is configured with, which falls
+/// back to `$VISUAL` and `$EDITOR`, and returns the edited message.
+fn edit_message(message_file: &Path, message: &str) -> Result<String> {
+    fs::write(
+        message_file,
+        format!(
+            "{message}\n\n# Please edit the commit message for your changes. Lines starting\n# with '#' will be ignored, and an empty message aborts the commit.\n"
+        ),
+    )?;
+
+    let editor = run_command("git", &["var", "GIT_EDITOR"])?;
+    let editor = editor.trim();
+    // the editor may come with arguments, so let the shell split it like git does
+    let path = message_file.to_string_lossy();
+    run_command_interactive("sh", &["-c", &format!("{editor} \"$@\""), editor, &path])?;
+
+    Ok(strip_comments(&fs::read_to_string(message_file)?))
+}
+
+/// Returns the file the message is written to for editing and committing,
+/// next to the `COMMIT_EDITMSG` of git
+fn get_message_file(git_client: &dyn GitClient) -> Result<PathBuf> {
+    Ok(git_client.get_git_dir()?.join("GPTCOMMIT_EDITMSG"))
+}
+
+pub(crate) async fn main(mut settings: Settings, args: CommitArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    if let Some(candidates) = args.candidates {
+        settings
+            .output
+            .get_or_insert_with(Default::default)
+            .candidates = Some(candidates);
+    }
+
+    let git_client = git::get_git_client(&settings);
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+    let output = git_client.get_diffs(&DiffBase::Head, &diff_settings)?;
+    let file_diffs = diff::parse(&output);
+    if file_diffs.is_empty() {
+        bail!("There are no staged changes to commit. Stage them with `git add` first.");
+    }
+
+    let client = get_llm_client(&settings);
+    let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+
+    let message_file = get_message_file(git_client.as_ref())?;
+    let mut commit_message = String::new();
+    let mut regenerate = true;
+    loop {
+        if regenerate {
+            println!(
+                "{}",
+                "🤖 Let's ask OpenAI to summarize those diffs! 🚀"
+                    .green()
+                    .bold()
+            );
+            let mut candidates = summarization_client
+                .get_commit_messages(&file_diffs)
+                .await?;
+            if settings.dry_run == Some(true) {
+                println!("{}", "Dry run, nothing was committed.".yellow());
+                return Ok(());
+            }
+            regenerate = false;
+            if candidates.len() > 1 && !args.yes {
+                match pick_candidate(&candidates)? {
+                    Some(idx) => commit_message = candidates.swap_remove(idx),
+                    None => {
+                        println!("{}", "Aborting commit.".red());
+                        return Ok(());
+                    }
+                }
+            } else {
+                commit_message = candidates.swap_remove(0);
+            }
+        }
+        println!("\n{}\n", commit_message.trim());
+
+        if args.yes {
+            break;
+        }
+        match ask_choice()? {
+            Choice::Accept => break,
+            Choice::Edit => {
+                let edited = edit_message(&message_file, &commit_message);
+                let _ = fs::remove_file(&message_file);
+                commit_message = edited?;
+                if commit_message.is_empty() {
+                    println!("{}", "Aborting commit due to empty commit message.".red());
+                    return Ok(());
+                }
+            }
+            Choice::Regenerate => regenerate = true,
+            Choice::Abort => {
+                println!("{}", "Aborting commit.".red());
+                return Ok(());
+            }
+        }
+    }
+
+    fs::write(&message_file, format!("{}\n", commit_message.trim()))?;
+    let path = message_file.to_string_lossy();
+    let mut git_args = vec!["commit", "-F", &path];
+    git_args.extend(args.git_args.iter().map(String::as_str));
+    // the message is already generated, keep an installed hook from adding to it
+    let result =
+        run_command_interactive_with_env("git", &git_args, &[("GPTCOMMIT__MESSAGE_MODE", "skip")]);
+    let _ = fs::remove_file(&message_file);
+
+    result
+}
@@ -0,0 +1,140 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use colored::Colorize;
+
+use std::time::Instant;
+
+use crate::diff;
+use crate::git::{self, DiffBase};
+use crate::llms::get_llm_client;
+use crate::output::{MessageOutput, OutputFormat};
+use crate::settings::Settings;
+use crate::summarize::{Explanation, SummarizationClient};
+
+/// Arguments for the Explain action
+#[derive(Args, Debug)]
+pub(crate) struct ExplainArgs {
+    /// Commit to explain, or a range of commits such as main..feature or
+    /// main...feature
+    rev: String,
+
+    /// Compare the explanation with the recorded commit messages and flag
+    /// mismatches
+    #[arg(long)]
+    compare: bool,
+
+    /// How to print the explanation
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// Print the prompts that would be sent to the model without sending them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// The summary and, when compared, the mismatches as plain text. The file
+/// summaries are left out, as the JSON output lists them on their own.
+fn explanation_message(explanation: &Explanation) -> String {
+    let mut message = explanation.summary.trim().to_string();
+    match explanation.mismatches.as_deref() {
+        Some([]) => message.push_str("\n\nThe recorded messages match the changes."),
+        Some(mismatches) => {
+            message.push_str("\n\nThe recorded messages may not match the changes:");
+            for mismatch in mismatches {
+                message.push_str(&format!("\n- {mismatch}"));
+            }
+        }
+        None => {}
+    }
+    message
+}
+
+pub(crate) async fn main(mut settings: Settings, args: ExplainArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let started = Instant::now();
+    let git_client = git::get_git_client(&settings);
+    let diff_base = git::get_diff_range(git_client.as_ref(), &args.rev)?;
+    debug!("Explaining {diff_base:?}");
+
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+    let output = git_client.get_diffs(&diff_base, &diff_settings)?;
+    let file_diffs = diff::parse(&output);
+    if file_diffs.is_empty() {
+        bail!("There are no changes in {} to explain.", args.rev);
+    }
+    let commit_messages = if args.compare {
+        let commits = match &diff_base {
+            DiffBase::Range(base, tip) => git_client.get_commits(base, tip)?,
+            _ => vec![git_client.get_commit(&args.rev)?],
+        };
+        Some(
+            commits
+                .iter()
+                .map(|commit| commit.message.trim())
+                .collect::<Vec<&str>>()
+                .join("\n\n"),
+        )
+    } else {
+        None
+    };
+
+    let client = get_llm_client(&settings);
+    let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+
+    eprintln!(
+        "{}",
+        "🤖 Let's ask OpenAI to explain those changes! 🚀"
+            .green()
+            .bold()
+    );
+    let explanation = summarization_client
+        .get_explanation(&file_diffs, commit_messages.as_deref())
+        .await?;
+    if settings.dry_run == Some(true) {
+        return Ok(());
+    }
+    if args.format == OutputFormat::Json {
+        println!(
+            "{}",
+            MessageOutput::new(
+                &explanation_message(&explanation),
+                summarization_client.report(),
+                started.elapsed()
+            )
+            .to_json()
+        );
+        return Ok(());
+    }
+
+    println!("{}\n{}\n", "Summary".bold(), explanation.summary);
+    println!("{}", "Files".bold());
+    for file_summary in &explanation.file_summaries {
+        if !file_summary.summary.is_empty() {
+            println!(
+                "[{}]\n{}",
+                file_summary.file_name,
+                file_summary.summary.trim()
+            );
+        }
+    }
+
+    match explanation.mismatches.as_deref() {
+        Some([]) => println!("\n{}", "The recorded messages match the changes.".green()),
+        Some(mismatches) => {
+            println!(
+                "\n{}",
+                "The recorded messages may not match the changes:"
+                    .yellow()
+                    .bold()
+            );
+            for mismatch in mismatches {
+                println!("{}", format!("- {mismatch}").yellow());
+            }
+        }
+        None => {}
+    }
+
+    Ok(())
+}
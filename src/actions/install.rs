@@ -1,9 +1,11 @@
 use std::fs::{self};
+use std::path::Path;
 
 #[cfg(unix)]
 use std::{fs::Permissions, os::unix::prelude::PermissionsExt};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use clap::Args;
 use colored::Colorize;
 
 use crate::{
@@ -13,7 +15,24 @@ use crate::{
     settings::{ModelProvider, OpenAISettings, Settings},
 };
 
-pub(crate) async fn main(settings: Settings) -> Result<()> {
+/// Arguments for the Install action
+#[derive(Args, Debug)]
+pub(crate) struct InstallArgs {
+    /// Also install a pre-commit hook that reviews the staged changes
+    #[arg(long)]
+    review: bool,
+}
+
+fn write_hook(hooks_path: &Path, name: &str, contents: &str) -> Result<()> {
+    let hook_path = hooks_path.join(name);
+    info!("Overwriting file at {}", hook_path.display());
+    fs::write(&hook_path, contents)?;
+    #[cfg(unix)]
+    fs::set_permissions(&hook_path, Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+pub(crate) async fn main(settings: Settings, args: InstallArgs) -> Result<()> {
     println!("{}", "Installing gptcommit hook...".green().bold());
 
     find_executable("git", "To use gptcommit, you must have git on your PATH")?;
@@ -26,18 +45,30 @@ pub(crate) async fn main(settings: Settings) -> Result<()> {
         "Found git hooks path for current git repo {}",
         hooks_path.display()
     );
-    let prepare_commit_msg_path = hooks_path.join("prepare-commit-msg");
+    // never clobber a pre-commit hook that isn't ours
+    let pre_commit_path = hooks_path.join("pre-commit");
+    if args.review
+        && pre_commit_path.exists()
+        && fs::read_to_string(&pre_commit_path)? != include_str!("../../pre-commit")
+    {
+        bail!(
+            "{} already exists and is not gptcommit's pre-commit hook. Add `gptcommit review` to it manually, or remove it and run `gptcommit install --review` again.",
+            pre_commit_path.display()
+        );
+    }
+
     println!(
         "Installing git hook to {}",
         hooks_path.display().to_string().bold()
     );
-    info!("Overwriting file at {}", prepare_commit_msg_path.display());
-    fs::write(
-        &prepare_commit_msg_path,
+    write_hook(
+        &hooks_path,
+        "prepare-commit-msg",
         include_str!("../../prepare-commit-msg"),
     )?;
-    #[cfg(unix)]
-    fs::set_permissions(&prepare_commit_msg_path, Permissions::from_mode(0o755))?;
+    if args.review {
+        write_hook(&hooks_path, "pre-commit", include_str!("../../pre-commit"))?;
+    }
 
     println!(
         "{}",
@@ -1,4 +1,11 @@
+pub(crate) mod changelog;
+pub(crate) mod commit;
 pub(crate) mod config;
+pub(crate) mod explain;
 pub(crate) mod install;
+pub(crate) mod pr;
 pub(crate) mod prepare_commit_msg;
+pub(crate) mod review;
+pub(crate) mod reword;
+pub(crate) mod summarize;
 pub(crate) mod uninstall;
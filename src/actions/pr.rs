@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use colored::Colorize;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::diff;
+use crate::git::{self, DiffBase};
+use crate::llms::get_llm_client;
+use crate::output::{MessageOutput, OutputFormat};
+use crate::settings::Settings;
+use crate::summarize::SummarizationClient;
+
+/// Arguments for the Pr action
+#[derive(Args, Debug)]
+pub(crate) struct PrArgs {
+    /// Branch the pull request is merged into. Defaults to the default branch of
+    /// the upstream remote
+    #[arg(long)]
+    base: Option<String>,
+
+    /// Branch or commit the pull request is made from
+    #[arg(long, default_value = "HEAD")]
+    head: String,
+
+    /// Write the title and description to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// How to print the title and description
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// Print the prompts that would be sent to the model without sending them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub(crate) async fn main(mut settings: Settings, args: PrArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let started = Instant::now();
+    let git_client = git::get_git_client(&settings);
+    let base_branch = match args.base {
+        Some(base) => base,
+        None => git::get_default_branch(git_client.as_ref())?,
+    };
+    // like pull requests, only show what the head adds since it forked off
+    let base = git_client.get_merge_base(&base_branch, &args.head)?;
+    debug!(
+        "Describing {}..{} forked from {base_branch}",
+        base, args.head
+    );
+
+    let commits = git_client.get_commits(&base, &args.head)?;
+    if commits.is_empty() {
+        bail!(
+            "There are no commits on {} that are not on {base_branch}.",
+            args.head
+        );
+    }
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+    let output = git_client.get_diffs(&DiffBase::Range(base, args.head.clone()), &diff_settings)?;
+    let file_diffs = diff::parse(&output);
+
+    let client = get_llm_client(&settings);
+    let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+
+    eprintln!(
+        "{}",
+        "🤖 Let's ask OpenAI to describe those commits! 🚀"
+            .green()
+            .bold()
+    );
+    let pr_message = summarization_client
+        .get_pr_message(&commits, &file_diffs)
+        .await?;
+    if settings.dry_run == Some(true) {
+        return Ok(());
+    }
+    let pr_message = match args.format {
+        OutputFormat::Text => pr_message,
+        OutputFormat::Json => format!(
+            "{}\n",
+            MessageOutput::new(
+                &pr_message,
+                summarization_client.report(),
+                started.elapsed()
+            )
+            .to_json()
+        ),
+    };
+
+    match args.output {
+        Some(path) => {
+            fs::write(&path, pr_message)?;
+            eprintln!("Wrote the pull request to {}", path.display());
+        }
+        None => print!("{pr_message}"),
+    }
+
+    Ok(())
+}
@@ -1,26 +1,28 @@
 use anyhow::Result;
-use clap::arg;
 use clap::ValueEnum;
 use colored::Colorize;
 
 use clap::Args;
 use strum_macros::Display;
 
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
 use std::fs;
+use std::str::FromStr;
 
 use std::path::PathBuf;
 
-use crate::git;
+use crate::diff;
+use crate::encoding;
+use crate::git::{self, DiffBase, GitClient};
 
-use crate::help::print_help_openai_api_key;
-use crate::llms::{llm_client::LlmClient, openai::OpenAIClient};
-use crate::settings::ModelProvider;
-
-use crate::settings::Settings;
+use crate::llms::get_llm_client;
+use crate::settings::{DiffSettings, MessageMode, Settings, TemplateMode};
 use crate::summarize::SummarizationClient;
-use crate::util::SplitPrefixInclusive;
 
-use crate::llms::tester_foobar::FooBarClient;
+lazy_static! {
+    static ref TEMPLATE_PLACEHOLDER: Regex = Regex::new(r"\{\{\s*(title|body)\s*\}\}").unwrap();
+}
 
 /// Enum representing the possible commit message sources
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Display, ValueEnum, Default)]
@@ -53,44 +55,165 @@ pub(crate) struct PrepareCommitMsgArgs {
     /// Debugging tool to mock git repo state
     #[arg(long)]
     git_diff_content: Option<PathBuf>,
+
+    /// Print the prompts that would be sent to the model without sending them
+    /// or changing the commit message
+    #[arg(long)]
+    dry_run: bool,
 }
-fn get_llm_client(settings: &Settings) -> Box<dyn LlmClient> {
-    match settings {
-        Settings {
-            model_provider: Some(ModelProvider::TesterFoobar),
-            ..
-        } => Box::new(FooBarClient::new().unwrap()),
-        Settings {
-            model_provider: Some(ModelProvider::OpenAI),
-            openai: Some(openai),
-            ..
-        } => {
-            let client = OpenAIClient::new(openai.to_owned());
-            if let Err(_e) = client {
-                print_help_openai_api_key();
-                panic!("OpenAI API key not found in config or environment");
-            }
-            Box::new(client.unwrap())
+/// Returns the commit the staged changes are diffed against.
+///
+/// `git commit --amend` passes HEAD as the commit, while `-c` and `-C` pass the
+/// commit whose message is reused. An amend replaces HEAD, so its diff starts
+/// from the parent of the amended commit to describe the commit as a whole.
+/// The hook can't tell an amend from `-c HEAD` or `-C HEAD`, so those are
+/// treated as amends too.
+fn get_diff_base(git_client: &dyn GitClient, args: &PrepareCommitMsgArgs) -> Result<DiffBase> {
+    let commit_sha = match (args.commit_source, args.commit_sha.as_deref()) {
+        (CommitSource::Commit, Some(commit_sha)) if !commit_sha.is_empty() => commit_sha,
+        _ => return Ok(DiffBase::Head),
+    };
+    let commit = git_client.resolve_commit(commit_sha)?;
+    match git_client.resolve_commit("HEAD") {
+        Ok(head) if head == commit => {
+            debug!("Amending {commit}, summarizing the changes since its parent");
+            Ok(DiffBase::ParentOf(commit))
         }
-        _ => panic!("Could not load LLM Client from config!"),
+        _ => Ok(DiffBase::Head),
     }
 }
 
-pub(crate) async fn main(settings: Settings, args: PrepareCommitMsgArgs) -> Result<()> {
-    match (args.commit_source, settings.allow_amend) {
-        (CommitSource::Empty, _) | (CommitSource::Commit, Some(true)) => {}
-        (CommitSource::Commit, _) => {
+/// Returns the paths listed under `Conflicts:` in the message git prepares for a
+/// merge. Recent versions of git comment the list out.
+fn get_conflicts(message: &str) -> Vec<String> {
+    let strip_comment = |line: &str| line.strip_prefix('#').unwrap_or(line).to_string();
+    message
+        .lines()
+        .map(strip_comment)
+        .skip_while(|line| line.trim() != "Conflicts:")
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .take_while(|line| line.starts_with('\t'))
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+/// Returns the lines of the commit message that git keeps, without comments
+pub(crate) fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Returns the comment lines of the commit message, as prepared by git
+fn get_comments(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Replaces the `{{ title }}` and `{{ body }}` placeholders of a commit template
+/// with the title and the body of `commit_message`. Returns `None` when the
+/// template has no placeholders.
+fn fill_template(template: &str, commit_message: &str) -> Option<String> {
+    if !TEMPLATE_PLACEHOLDER.is_match(template) {
+        return None;
+    }
+    let (title, body) = commit_message
+        .split_once("\n\n")
+        .unwrap_or((commit_message, ""));
+    let filled = TEMPLATE_PLACEHOLDER.replace_all(template, |caps: &Captures| match &caps[1] {
+        "title" => title.trim().to_string(),
+        _ => body.trim().to_string(),
+    });
+    Some(filled.into_owned())
+}
+
+/// Comments out the alternative commit messages, so one can be picked in the
+/// editor. Only the titles are listed when the candidates share their body.
+fn format_alternatives(commit_message: &str, alternatives: &[String]) -> String {
+    if alternatives.is_empty() {
+        return String::new();
+    }
+    let split = |message: &str| {
+        let (title, body) = message.split_once("\n\n").unwrap_or((message, ""));
+        (title.trim().to_string(), body.trim().to_string())
+    };
+    let (_, body) = split(commit_message);
+    if alternatives
+        .iter()
+        .all(|alternative| split(alternative).1 == body)
+    {
+        let titles: String = alternatives
+            .iter()
+            .map(|alternative| format!("# {}\n", split(alternative).0))
+            .collect();
+        return format!(
+            "\n# Alternative titles, uncomment one to use it instead of the first line:\n{titles}"
+        );
+    }
+    let messages = alternatives
+        .iter()
+        .map(|alternative| {
+            alternative
+                .trim()
+                .lines()
+                .map(|line| format!("# {line}").trim_end().to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n#\n# ---\n#\n");
+    format!("\n# Alternative messages, uncomment one to use it instead of the message above:\n{messages}\n")
+}
+
+pub(crate) async fn main(mut settings: Settings, args: PrepareCommitMsgArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let template_mode =
+        TemplateMode::from_str(settings.template_mode.as_deref().unwrap_or_default())
+            .unwrap_or_default();
+    let message_mode = MessageMode::from_str(settings.message_mode.as_deref().unwrap_or_default())
+        .unwrap_or_default();
+    match args.commit_source {
+        CommitSource::Empty => {}
+        CommitSource::Commit if settings.allow_amend == Some(true) => {}
+        CommitSource::Merge if settings.allow_merge == Some(true) => {}
+        CommitSource::Squash if settings.allow_squash == Some(true) => {}
+        CommitSource::Template if template_mode != TemplateMode::Skip => {}
+        CommitSource::Message if message_mode != MessageMode::Skip => {}
+        CommitSource::Commit => {
             println!("🤖 Skipping gptcommit since we're amending a commit. Change this behavior with `gptcommit config set allow_amend true`");
             return Ok(());
         }
-        _ => {
-            println!(
-                "🤖 Skipping gptcommit because the githook isn't set up for the \"{}\" commit mode.", args.commit_source
-            );
+        CommitSource::Merge => {
+            println!("🤖 Skipping gptcommit since we're creating a merge commit. Change this behavior with `gptcommit config set allow_merge true`");
+            return Ok(());
+        }
+        CommitSource::Squash => {
+            println!("🤖 Skipping gptcommit since we're squashing commits. Change this behavior with `gptcommit config set allow_squash true`");
+            return Ok(());
+        }
+        CommitSource::Message => {
+            println!("🤖 Skipping gptcommit since the commit message was given with -m or -F. Change this behavior with `gptcommit config set message_mode append`");
+            return Ok(());
+        }
+        CommitSource::Template => {
+            println!("🤖 Skipping gptcommit since the commit starts from a template. Change this behavior with `gptcommit config set template_mode placeholders`");
             return Ok(());
         }
     };
 
+    let git_client = git::get_git_client(&settings);
+    let diff_base = get_diff_base(git_client.as_ref(), &args)?;
+
     let client = get_llm_client(&settings);
     let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
 
@@ -101,33 +224,180 @@ pub(crate) async fn main(settings: Settings, args: PrepareCommitMsgArgs) -> Resu
             .bold()
     );
 
-    let output = if let Some(git_diff_output) = args.git_diff_content {
-        fs::read_to_string(git_diff_output)?
+    let original_message: String = if args.commit_msg_file.is_file() {
+        fs::read_to_string(&args.commit_msg_file)?
     } else {
-        git::get_diffs()?
+        String::new()
     };
 
-    let file_diffs = output.split_prefix_inclusive("\ndiff --git ");
-    let commit_message = summarization_client.get_commit_message(file_diffs).await?;
-
-    // prepend output to commit message
-    let mut original_message: String = if args.commit_msg_file.is_file() {
-        fs::read_to_string(&args.commit_msg_file)?
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+    let output = if let Some(git_diff_output) = args.git_diff_content {
+        encoding::decode_diff(&fs::read(git_diff_output)?)
+    } else if args.commit_source == CommitSource::Merge {
+        // only the files with resolved conflicts differ from what the merged commits bring
+        let conflicts = get_conflicts(&original_message);
+        if conflicts.is_empty() {
+            String::new()
+        } else {
+            let pathspec = conflicts
+                .iter()
+                .map(|path| format!(":(literal){path}"))
+                .collect();
+            let diff_settings = DiffSettings {
+                pathspec: Some(pathspec),
+                ..diff_settings
+            };
+            git_client.get_diffs(&diff_base, &diff_settings)?
+        }
     } else {
-        String::new()
+        git_client.get_diffs(&diff_base, &diff_settings)?
     };
-    if settings.allow_amend.unwrap_or(false) {
-        original_message = original_message
-            .lines()
-            .map(|l| format!("# {l}"))
-            .collect::<Vec<String>>()
-            .join("\n");
-        original_message = format!("### BEGIN GIT COMMIT BEFORE AMEND\n{original_message}\n### END GIT COMMIT BEFORE AMEND\n");
+    let file_diffs = diff::parse(&output);
+
+    let mut alternatives = Vec::new();
+    let commit_message = match args.commit_source {
+        CommitSource::Merge => {
+            let mut commits = Vec::new();
+            for merge_head in git_client.get_merge_heads()? {
+                commits.extend(git_client.get_commits("HEAD", &merge_head)?);
+            }
+            debug!("Summarizing {} merged commits", commits.len());
+            let kept_message = strip_comments(&original_message);
+            let title = kept_message.lines().next().unwrap_or("Merge");
+            summarization_client
+                .get_merge_message(title, &commits, &file_diffs)
+                .await?
+        }
+        CommitSource::Squash => {
+            summarization_client
+                .get_squash_message(&strip_comments(&original_message), &file_diffs)
+                .await?
+        }
+        CommitSource::Template if template_mode == TemplateMode::Prompt => {
+            summarization_client
+                .get_template_message(&strip_comments(&original_message), &file_diffs)
+                .await?
+        }
+        CommitSource::Message => {
+            summarization_client
+                .get_commit_body(&strip_comments(&original_message), &file_diffs)
+                .await?
+        }
+        _ => {
+            let mut messages = summarization_client
+                .get_commit_messages(&file_diffs)
+                .await?;
+            alternatives = messages.split_off(1);
+            messages.remove(0)
+        }
+    };
+    if settings.dry_run == Some(true) {
+        println!(
+            "{}",
+            "Dry run, the commit message was left untouched.".yellow()
+        );
+        return Ok(());
     }
-    fs::write(
-        &args.commit_msg_file,
-        format!("{commit_message}\n{original_message}"),
-    )?;
+    let commit_message = format!(
+        "{commit_message}{}",
+        format_alternatives(&commit_message, &alternatives)
+    );
+
+    let new_message = match args.commit_source {
+        // the message git prepared is summarized, only its comments are kept
+        CommitSource::Merge | CommitSource::Squash => {
+            format!("{commit_message}\n{}", get_comments(&original_message))
+        }
+        CommitSource::Template if template_mode == TemplateMode::Prompt => {
+            format!("{commit_message}\n{}", get_comments(&original_message))
+        }
+        // the subject line given by the author is kept as is
+        CommitSource::Message => {
+            let user_message = strip_comments(&original_message);
+            let kept_message = match message_mode {
+                MessageMode::Expand => user_message.lines().next().unwrap_or_default(),
+                _ => &user_message,
+            };
+            format!(
+                "{kept_message}\n\n{commit_message}\n{}",
+                get_comments(&original_message)
+            )
+        }
+        CommitSource::Template => {
+            fill_template(&original_message, &commit_message).unwrap_or_else(|| {
+                warn!("The commit template has no {{{{ title }}}} or {{{{ body }}}} placeholder");
+                format!("{commit_message}\n{original_message}")
+            })
+        }
+        // prepend output to commit message
+        _ if settings.allow_amend.unwrap_or(false) => {
+            let original_message = original_message
+                .lines()
+                .map(|l| format!("# {l}"))
+                .collect::<Vec<String>>()
+                .join("\n");
+            format!("{commit_message}\n### BEGIN GIT COMMIT BEFORE AMEND\n{original_message}\n### END GIT COMMIT BEFORE AMEND\n")
+        }
+        _ => format!("{commit_message}\n{original_message}"),
+    };
+    fs::write(&args.commit_msg_file, new_message)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_conflicts() {
+        let message = "Merge branch 'feature'\n\n# Conflicts:\n#\tsrc/main.rs\n#\tREADME.md\n#\n# It looks like you may be committing a merge.\n";
+        assert_eq!(get_conflicts(message), ["src/main.rs", "README.md"]);
+
+        let message = "Merge branch 'feature'\n\nConflicts:\n\tsrc/lib.rs\n";
+        assert_eq!(get_conflicts(message), ["src/lib.rs"]);
+
+        assert!(get_conflicts("Merge branch 'feature'\n").is_empty());
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let template = "{{ title }}\n\nWhy:\n{{body}}\n\nTesting:\n";
+        assert_eq!(
+            fill_template(
+                template,
+                "feat: Add a flag\n\n- Add a flag\n- Document it\n"
+            )
+            .as_deref(),
+            Some("feat: Add a flag\n\nWhy:\n- Add a flag\n- Document it\n\nTesting:\n")
+        );
+        assert_eq!(fill_template("Why:\nTesting:\n", "Add a flag"), None);
+    }
+
+    #[test]
+    fn test_format_alternatives() {
+        assert_eq!(format_alternatives("Add a flag\n\n- body\n", &[]), "");
+        assert_eq!(
+            format_alternatives(
+                "Add a flag\n\n- body\n",
+                &["Add the --flag option\n\n- body\n".to_string()]
+            ),
+            "\n# Alternative titles, uncomment one to use it instead of the first line:\n# Add the --flag option\n"
+        );
+        assert_eq!(
+            format_alternatives(
+                "Add a flag\n\n- body\n",
+                &["Add an option\n\n- other body\n".to_string()]
+            ),
+            "\n# Alternative messages, uncomment one to use it instead of the message above:\n# Add an option\n#\n# - other body\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(
+            strip_comments("Merge branch 'feature'\n\n# Conflicts:\n#\tsrc/main.rs\n"),
+            "Merge branch 'feature'"
+        );
+    }
+}
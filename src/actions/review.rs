@@ -0,0 +1,121 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use colored::Colorize;
+
+use std::str::FromStr;
+use std::time::Instant;
+
+use crate::diff;
+use crate::git::{self, DiffBase};
+use crate::llms::get_llm_client;
+use crate::output::{MessageOutput, OutputFormat};
+use crate::review::ReviewFinding;
+use crate::settings::{ReviewSeverity, Settings, REVIEW_NEVER_FAIL};
+use crate::summarize::SummarizationClient;
+
+/// Arguments for the Review action
+#[derive(Args, Debug)]
+pub(crate) struct ReviewArgs {
+    /// Lowest severity of the findings that fail the review, one of low, medium,
+    /// high or never, overriding `review.fail_on`
+    #[arg(long)]
+    fail_on: Option<String>,
+
+    /// How to print the findings
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// Print the prompts that would be sent to the model without sending them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+fn print_finding(finding: &ReviewFinding) {
+    let severity = finding.severity.to_string();
+    let severity = match finding.severity {
+        ReviewSeverity::High => severity.red().bold(),
+        ReviewSeverity::Medium => severity.yellow().bold(),
+        ReviewSeverity::Low => severity.normal(),
+    };
+    println!(
+        "{}: {severity}: {}",
+        finding.location().bold(),
+        finding.message
+    );
+}
+
+pub(crate) async fn main(mut settings: Settings, args: ReviewArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let started = Instant::now();
+    let review_settings = settings.review.clone().unwrap_or_default();
+    let fail_on = args.fail_on.or(review_settings.fail_on).unwrap_or_default();
+    let threshold = if fail_on == REVIEW_NEVER_FAIL {
+        None
+    } else {
+        Some(
+            ReviewSeverity::from_str(&fail_on)
+                .map_err(|_| anyhow!("Invalid review severity: {fail_on}."))?,
+        )
+    };
+
+    let git_client = git::get_git_client(&settings);
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+    let output = git_client.get_diffs(&DiffBase::Head, &diff_settings)?;
+    let file_diffs = diff::parse(&output);
+    if file_diffs.is_empty() {
+        println!("There are no staged changes to review.");
+        return Ok(());
+    }
+
+    let client = get_llm_client(&settings);
+    let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+
+    eprintln!(
+        "{}",
+        "🤖 Let's ask OpenAI to review those diffs! 🚀"
+            .green()
+            .bold()
+    );
+    let findings = summarization_client.get_review(&file_diffs).await?;
+    if settings.dry_run == Some(true) {
+        return Ok(());
+    }
+    if args.format == OutputFormat::Json {
+        // one finding per line, as `path:line: severity: message`
+        let message = findings
+            .iter()
+            .map(ReviewFinding::to_string)
+            .collect::<Vec<String>>()
+            .join("\n");
+        println!(
+            "{}",
+            MessageOutput::new(&message, summarization_client.report(), started.elapsed())
+                .to_json()
+        );
+    } else if findings.is_empty() {
+        println!("{}", "No findings.".green());
+        return Ok(());
+    } else {
+        for finding in &findings {
+            print_finding(finding);
+        }
+    }
+
+    let failing = findings
+        .iter()
+        .filter(|finding| threshold.is_some_and(|threshold| finding.severity >= threshold))
+        .count();
+    if failing > 0 {
+        bail!(
+            "Found {failing} of {} findings of {fail_on} severity or higher.",
+            findings.len()
+        );
+    }
+    if args.format == OutputFormat::Text {
+        println!("Found {} findings.", findings.len());
+    }
+
+    Ok(())
+}
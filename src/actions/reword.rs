@@ -0,0 +1,239 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use colored::Colorize;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::diff;
+use crate::git::{self, CommitInfo, DiffBase, GitClient};
+use crate::llms::get_llm_client;
+use crate::settings::Settings;
+use crate::summarize::SummarizationClient;
+
+/// Arguments for the Reword action
+#[derive(Args, Debug)]
+pub(crate) struct RewordArgs {
+    /// Commit the reworded commits start after. Defaults to where the branch
+    /// forked off the default branch
+    #[arg(long)]
+    base: Option<String>,
+
+    /// Print the old and new messages without rewriting any commit. Unlike
+    /// --dry-run, the messages are generated by the model
+    #[arg(long)]
+    preview: bool,
+
+    /// Print the prompts that would be sent to the model without sending them
+    /// or rewriting any commit
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Rewrite the commits without asking
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// The branch the current branch is pushed to and pulled from
+const UPSTREAM: &str = "@{upstream}";
+
+lazy_static! {
+    static ref TRAILER_LINE: Regex = Regex::new(r"^[A-Za-z0-9-]+:\s").unwrap();
+}
+
+/// A commit to reword, with the message generated from its own diff
+struct Rewording {
+    commit: CommitInfo,
+    parent: String,
+    message: String,
+}
+
+/// Returns the parent of `commit`, refusing merge commits, which can't be
+/// replayed one diff at a time.
+fn get_parent(git_client: &dyn GitClient, commit: &CommitInfo) -> Result<String> {
+    let parents = git_client.get_parents(&commit.id)?;
+    match parents.as_slice() {
+        [parent] => Ok(parent.to_string()),
+        [] => bail!("Can't reword the root commit {}.", commit.id),
+        _ => bail!(
+            "Can't reword the merge commit {}. Pass a --base after it.",
+            commit.id
+        ),
+    }
+}
+
+/// Refuses to rewrite commits that were already pushed to the default branch of
+/// a remote or to the upstream of the current branch, as rewriting them would
+/// rewrite published history. Commits pushed to other branches are not protected.
+fn check_not_published(git_client: &dyn GitClient, commits: &[CommitInfo]) -> Result<()> {
+    let mut branches = git::get_remote_default_branches(git_client)?
+        .into_iter()
+        .map(|branch| (branch.clone(), branch))
+        .collect::<Vec<(String, String)>>();
+    if git_client.resolve_commit(UPSTREAM).is_ok() {
+        branches.push((
+            UPSTREAM.to_string(),
+            "the upstream of the current branch".to_string(),
+        ));
+    }
+    for (branch, name) in branches {
+        let unpublished = git_client.get_commits(&branch, "HEAD")?;
+        if let Some(commit) = commits.iter().find(|commit| !unpublished.contains(commit)) {
+            bail!(
+                "Refusing to reword {} \"{}\", which is already on {name}. Pass a --base after it.",
+                &commit.id[..7.min(commit.id.len())],
+                commit.subject()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the trailers ending `message`, such as `Signed-off-by:` or
+/// `Change-Id:`: its last paragraph when every line of it is a trailer or
+/// continues one.
+fn get_trailers(message: &str) -> Option<&str> {
+    let (_, last_paragraph) = message.trim_end().rsplit_once("\n\n")?;
+    let is_trailer = |line: &str| TRAILER_LINE.is_match(line) || line.starts_with([' ', '\t']);
+    (TRAILER_LINE.is_match(last_paragraph) && last_paragraph.lines().all(is_trailer))
+        .then_some(last_paragraph)
+}
+
+/// Asks the user to confirm the rewrite. Reaching the end of stdin declines.
+fn confirm(count: usize) -> Result<bool> {
+    print!(
+        "{} ",
+        format!("Reword {count} commits with these messages? [y/N]").bold()
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn print_rewording(rewording: &Rewording) {
+    let short_id = &rewording.commit.id[..7.min(rewording.commit.id.len())];
+    println!("{}", short_id.yellow());
+    for line in rewording.commit.message.lines() {
+        println!("{}", format!("- {line}").trim_end().red());
+    }
+    for line in rewording.message.lines() {
+        println!("{}", format!("+ {line}").trim_end().green());
+    }
+    println!();
+}
+
+pub(crate) async fn main(mut settings: Settings, args: RewordArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let git_client = git::get_git_client(&settings);
+    let base = match args.base {
+        Some(base) => base,
+        None => {
+            let default_branch = git::get_default_branch(git_client.as_ref())?;
+            git_client.get_merge_base(&default_branch, "HEAD")?
+        }
+    };
+    let head = git_client.resolve_commit("HEAD")?;
+    let commits = git_client.get_commits(&base, &head)?;
+    if commits.is_empty() {
+        bail!("There are no commits after {base} to reword.");
+    }
+    check_not_published(git_client.as_ref(), &commits)?;
+    // refuse merges before asking the model about any commit
+    let parents = commits
+        .iter()
+        .map(|commit| get_parent(git_client.as_ref(), commit))
+        .collect::<Result<Vec<String>>>()?;
+
+    let client = get_llm_client(&settings);
+    let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+
+    println!(
+        "{}",
+        "🤖 Let's ask OpenAI to summarize those commits! 🚀"
+            .green()
+            .bold()
+    );
+    let mut rewordings = Vec::new();
+    for (commit, parent) in commits.into_iter().zip(parents).rev() {
+        let diff_base = DiffBase::Range(parent.clone(), commit.id.clone());
+        let file_diffs = diff::parse(&git_client.get_diffs(&diff_base, &diff_settings)?);
+        let message = if file_diffs.is_empty() {
+            // nothing to summarize, e.g. for an empty commit
+            commit.message.clone()
+        } else {
+            let mut messages = summarization_client
+                .get_commit_messages(&file_diffs)
+                .await?;
+            let message = messages.swap_remove(0).trim().to_string();
+            match get_trailers(&commit.message) {
+                Some(trailers) if !message.ends_with(trailers) => {
+                    format!("{message}\n\n{trailers}")
+                }
+                _ => message,
+            }
+        };
+        let rewording = Rewording {
+            commit,
+            parent,
+            message,
+        };
+        // a dry run only has placeholder messages to show
+        if settings.dry_run != Some(true) {
+            print_rewording(&rewording);
+        }
+        rewordings.push(rewording);
+    }
+
+    if args.preview || settings.dry_run == Some(true) {
+        return Ok(());
+    }
+    if !args.yes && !confirm(rewordings.len())? {
+        println!("{}", "Aborting reword.".red());
+        return Ok(());
+    }
+
+    let mut rewritten: HashMap<&str, String> = HashMap::new();
+    for rewording in &rewordings {
+        let parent = rewritten
+            .get(rewording.parent.as_str())
+            .map_or(rewording.parent.as_str(), String::as_str);
+        let new_id = git_client.reword_commit(
+            &rewording.commit.id,
+            parent,
+            &format!("{}\n", rewording.message),
+        )?;
+        rewritten.insert(&rewording.commit.id, new_id);
+    }
+    let new_head = &rewritten[head.as_str()];
+    // the trees are unchanged, so the index and work tree stay as they are
+    git_client.update_head(new_head, &head, "gptcommit: reword")?;
+    println!("Reworded {} commits.", rewordings.len());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_trailers() {
+        assert_eq!(
+            get_trailers("wip\n\nSigned-off-by: A <a@example.com>\nChange-Id: I123\n"),
+            Some("Signed-off-by: A <a@example.com>\nChange-Id: I123")
+        );
+        assert_eq!(
+            get_trailers("wip\n\nCo-authored-by: A\n  <a@example.com>"),
+            Some("Co-authored-by: A\n  <a@example.com>")
+        );
+        assert_eq!(get_trailers("Fixes: the parser"), None);
+        assert_eq!(get_trailers("wip\n\nNote: this is\nnot a trailer"), None);
+    }
+}
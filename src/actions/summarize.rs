@@ -0,0 +1,109 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use colored::Colorize;
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::diff;
+use crate::encoding;
+use crate::git::{self, DiffBase};
+use crate::llms::get_llm_client;
+use crate::output::{MessageOutput, OutputFormat};
+use crate::settings::Settings;
+use crate::summarize::SummarizationClient;
+
+/// Arguments for the Summarize action
+#[derive(Args, Debug)]
+pub(crate) struct SummarizeArgs {
+    /// Summarize the staged changes
+    #[arg(long, conflicts_with_all = ["rev", "range", "file"])]
+    staged: bool,
+
+    /// Summarize the changes of a commit
+    #[arg(long, conflicts_with_all = ["range", "file"])]
+    rev: Option<String>,
+
+    /// Summarize the changes of a range of commits, such as main..feature or
+    /// main...feature
+    #[arg(long, conflicts_with = "file")]
+    range: Option<String>,
+
+    /// Read the diff from this file, or from stdin with `-`. Without any of
+    /// these options the diff is read from stdin. Only diffs in the format of
+    /// `git diff` are read, such as those of `git diff --no-index`
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// How to print the generated message
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// Print the prompts that would be sent to the model without sending them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+fn read_stdin() -> Result<String> {
+    let mut stdin = io::stdin();
+    if stdin.is_terminal() {
+        bail!("Pipe a diff to stdin, or pass --staged, --rev, --range or --file.");
+    }
+    let mut input = Vec::new();
+    stdin.read_to_end(&mut input)?;
+    Ok(encoding::decode_diff(&input))
+}
+
+pub(crate) async fn main(mut settings: Settings, args: SummarizeArgs) -> Result<()> {
+    if args.dry_run {
+        settings.dry_run = Some(true);
+    }
+    let started = Instant::now();
+    let git_client = git::get_git_client(&settings);
+    let diff_settings = settings.diff.clone().unwrap_or_default();
+    let output = if args.staged {
+        git_client.get_diffs(&DiffBase::Head, &diff_settings)?
+    } else if let Some(rev) = args.rev.as_deref().or(args.range.as_deref()) {
+        let diff_base = git::get_diff_range(git_client.as_ref(), rev)?;
+        git_client.get_diffs(&diff_base, &diff_settings)?
+    } else {
+        match args.file {
+            Some(path) if path.as_os_str() != "-" => encoding::decode_diff(&fs::read(path)?),
+            _ => read_stdin()?,
+        }
+    };
+    let file_diffs = diff::parse(&output);
+    if file_diffs.is_empty() {
+        bail!("There are no changes to summarize. Only diffs in the format of `git diff` are read, such as those of `git diff --no-index old new`.");
+    }
+
+    let client = get_llm_client(&settings);
+    let summarization_client = SummarizationClient::new(settings.to_owned(), client)?;
+
+    eprintln!(
+        "{}",
+        "🤖 Let's ask OpenAI to summarize those diffs! 🚀"
+            .green()
+            .bold()
+    );
+    let mut messages = summarization_client
+        .get_commit_messages(&file_diffs)
+        .await?;
+    let message = messages.swap_remove(0);
+    if settings.dry_run == Some(true) {
+        return Ok(());
+    }
+
+    match args.format {
+        OutputFormat::Text => println!("{}", message.trim_end()),
+        OutputFormat::Json => {
+            let output =
+                MessageOutput::new(&message, summarization_client.report(), started.elapsed());
+            println!("{}", output.to_json());
+        }
+    }
+
+    Ok(())
+}
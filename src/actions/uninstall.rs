@@ -40,5 +40,14 @@ pub(crate) async fn main(_settings: Settings) -> Result<()> {
         }
     }
 
+    // only installed with `install --review`, other pre-commit hooks are left alone
+    let pre_commit_path = hooks_path.join("pre-commit");
+    if pre_commit_path.exists()
+        && fs::read_to_string(&pre_commit_path)? == include_str!("../../pre-commit")
+    {
+        info!("Removing file at {}", pre_commit_path.display());
+        fs::remove_file(&pre_commit_path)?;
+    }
+
     Ok(())
 }
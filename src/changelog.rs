@@ -0,0 +1,209 @@
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::git::CommitInfo;
+
+/// Release notes in the keep-a-changelog style
+pub(crate) static CHANGELOG_TEMPLATE: &str = include_str!("../templates/changelog.tera");
+
+/// Sections of the release notes in the order they are rendered, with the
+/// conventional commit types they collect
+const SECTIONS: [(&str, &[&str]); 5] = [
+    ("Added", &["feat"]),
+    ("Changed", &["perf", "refactor", "revert", "style"]),
+    ("Fixed", &["fix"]),
+    ("Documentation", &["docs"]),
+    ("Maintenance", &["build", "chore", "ci", "test"]),
+];
+
+/// Section of the commits without a known conventional commit type
+const OTHER_SECTION: &str = "Other";
+
+/// Subjects of commits that don't belong in release notes
+const SKIPPED_SUBJECT_PREFIXES: [&str; 4] = ["Merge ", "fixup! ", "squash! ", "amend! "];
+
+lazy_static! {
+    static ref CONVENTIONAL_SUBJECT: Regex = Regex::new(
+        r"^(?P<type>[a-zA-Z]+)(?:\((?P<scope>[^)]*)\))?(?P<breaking>!)?:\s*(?P<subject>\S.*)$"
+    )
+    .unwrap();
+}
+
+/// A commit as listed in the release notes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ChangelogEntry {
+    pub id: String,
+    pub short_id: String,
+    /// The conventional commit type, such as `feat` or `fix`
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    /// The subject without its conventional commit prefix
+    pub subject: String,
+    pub breaking: bool,
+}
+
+impl ChangelogEntry {
+    /// Parses the conventional commit prefix of the subject, such as the `feat: `
+    /// gptcommit writes or `fix(parser)!: `. A `BREAKING CHANGE:` footer also
+    /// marks the commit as breaking.
+    pub(crate) fn parse(commit: &CommitInfo) -> Self {
+        let subject = commit.subject().trim();
+        let breaking_footer = commit.message.lines().any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+        let short_id = commit.id.chars().take(7).collect();
+        match CONVENTIONAL_SUBJECT.captures(subject) {
+            Some(caps) => Self {
+                id: commit.id.clone(),
+                short_id,
+                kind: Some(caps["type"].to_lowercase()),
+                scope: caps
+                    .name("scope")
+                    .map(|scope| scope.as_str().trim().to_string())
+                    .filter(|scope| !scope.is_empty()),
+                subject: caps["subject"].to_string(),
+                breaking: caps.name("breaking").is_some() || breaking_footer,
+            },
+            None => Self {
+                id: commit.id.clone(),
+                short_id,
+                kind: None,
+                scope: None,
+                subject: subject.to_string(),
+                breaking: breaking_footer,
+            },
+        }
+    }
+}
+
+/// A group of commits in the release notes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ChangelogSection {
+    pub name: String,
+    /// Summary of the section written by the model, empty unless requested
+    pub summary: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Groups the commits, given newest first, into sections listing the oldest
+/// commit first. Merges and fixups are left out, as are empty sections.
+pub(crate) fn group_commits(commits: &[CommitInfo]) -> Vec<ChangelogSection> {
+    let mut sections: Vec<ChangelogSection> = SECTIONS
+        .iter()
+        .map(|(name, _)| name)
+        .chain([&OTHER_SECTION])
+        .map(|name| ChangelogSection {
+            name: name.to_string(),
+            summary: String::new(),
+            entries: vec![],
+        })
+        .collect();
+
+    for commit in commits.iter().rev() {
+        if SKIPPED_SUBJECT_PREFIXES
+            .iter()
+            .any(|prefix| commit.subject().starts_with(prefix))
+        {
+            continue;
+        }
+        let entry = ChangelogEntry::parse(commit);
+        let idx = entry
+            .kind
+            .as_deref()
+            .and_then(|kind| SECTIONS.iter().position(|(_, kinds)| kinds.contains(&kind)))
+            .unwrap_or(SECTIONS.len());
+        sections[idx].entries.push(entry);
+    }
+
+    sections.retain(|section| !section.entries.is_empty());
+    sections
+}
+
+/// Renders the release notes with the Tera `template`
+pub(crate) fn render(
+    template: &str,
+    release: &str,
+    date: &str,
+    sections: &[ChangelogSection],
+) -> Result<String> {
+    let mut context = Context::new();
+    context.insert("release", release);
+    context.insert("date", date);
+    context.insert("sections", sections);
+    Ok(Tera::one_off(template, &context, false)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(id: &str, message: &str) -> CommitInfo {
+        CommitInfo {
+            id: id.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = ChangelogEntry::parse(&commit("1234567890", "feat: Add a flag"));
+        assert_eq!(entry.kind.as_deref(), Some("feat"));
+        assert_eq!(entry.scope, None);
+        assert_eq!(entry.subject, "Add a flag");
+        assert_eq!(entry.short_id, "1234567");
+        assert!(!entry.breaking);
+
+        let entry = ChangelogEntry::parse(&commit("1", "Fix(parser)!: Reject tabs"));
+        assert_eq!(entry.kind.as_deref(), Some("fix"));
+        assert_eq!(entry.scope.as_deref(), Some("parser"));
+        assert!(entry.breaking);
+
+        let entry = ChangelogEntry::parse(&commit(
+            "1",
+            "refactor: Rename settings\n\nBREAKING CHANGE: the old keys are gone",
+        ));
+        assert!(entry.breaking);
+
+        let entry = ChangelogEntry::parse(&commit("1", "Update README: typos"));
+        assert_eq!(entry.kind, None);
+        assert_eq!(entry.subject, "Update README: typos");
+    }
+
+    #[test]
+    fn test_group_commits_and_render() {
+        let commits = [
+            commit("4", "Merge branch 'feature'"),
+            commit("3", "fix: Handle empty diffs"),
+            commit("2", "Tweak wording"),
+            commit("1", "feat(cli)!: Add a changelog command"),
+            commit("0", "feat: Add a flag"),
+        ];
+        let sections = group_commits(&commits);
+        let names: Vec<&str> = sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Added", "Fixed", "Other"]);
+        assert_eq!(sections[0].entries[0].subject, "Add a flag");
+
+        assert_eq!(
+            render(CHANGELOG_TEMPLATE, "1.2.0", "2026-10-19", &sections).unwrap(),
+            "## [1.2.0] - 2026-10-19
+
+### Added
+
+- Add a flag
+- **BREAKING:** **cli:** Add a changelog command
+
+### Fixed
+
+- Handle empty diffs
+
+### Other
+
+- Tweak wording
+"
+        );
+    }
+}
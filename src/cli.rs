@@ -1,4 +1,8 @@
-use crate::actions::{config::ConfigArgs, prepare_commit_msg::PrepareCommitMsgArgs};
+use crate::actions::{
+    changelog::ChangelogArgs, commit::CommitArgs, config::ConfigArgs, explain::ExplainArgs,
+    install::InstallArgs, pr::PrArgs, prepare_commit_msg::PrepareCommitMsgArgs, review::ReviewArgs,
+    reword::RewordArgs, summarize::SummarizeArgs,
+};
 use clap::{Parser, Subcommand};
 
 /// Represents the main command-line interface for the application.
@@ -18,9 +22,24 @@ pub(crate) struct GptcommitCLI {
 #[derive(Subcommand, Debug)]
 pub(crate) enum Action {
     /// Install the git hook
-    Install,
+    Install(InstallArgs),
     /// Uninstall the git hook
     Uninstall,
+    /// Generate a commit message for the staged changes and commit them
+    Commit(CommitArgs),
+    /// Print a commit message for a diff from stdin, a file, the staged changes
+    /// or existing commits
+    Summarize(SummarizeArgs),
+    /// Review the staged changes for likely bugs, leftover debug code and missing tests
+    Review(ReviewArgs),
+    /// Write a pull request title and description for the commits on a branch
+    Pr(PrArgs),
+    /// Regenerate the messages of the commits on a branch
+    Reword(RewordArgs),
+    /// Explain what a commit or a range of commits changed
+    Explain(ExplainArgs),
+    /// Draft release notes from the commits between two refs
+    Changelog(ChangelogArgs),
     /// Read and modify settings
     Config(ConfigArgs),
     /// Run on the prepare-commit-msg hook
@@ -1,12 +1,20 @@
 use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Result;
+use std::io::Write;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::thread;
 use which::which;
 /// Runs the command with the given arguments and returns its stdout if the command
 /// exits successfully. If the command fails, returns an error.
 pub(crate) fn run_command(cmd: &str, args: &[&str]) -> Result<String> {
+    let stdout = run_command_raw(cmd, args)?;
+    String::from_utf8(stdout).map_err(|_| anyhow!("The output of `{}` is not valid UTF-8", cmd))
+}
+
+/// Runs the command like [`run_command`], returning its stdout as raw bytes.
+pub(crate) fn run_command_raw(cmd: &str, args: &[&str]) -> Result<Vec<u8>> {
     let output = Command::new(cmd).args(args).output()?;
 
     if !output.status.success() {
@@ -14,8 +22,46 @@ pub(crate) fn run_command(cmd: &str, args: &[&str]) -> Result<String> {
         bail!("{}", stderr);
     }
 
-    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
-    Ok(stdout)
+    Ok(output.stdout)
+}
+
+/// Runs the command like [`run_command`], writing `input` to its stdin.
+pub(crate) fn run_command_with_input(cmd: &str, args: &[&str], input: &[u8]) -> Result<String> {
+    run_command_with_env(cmd, args, &[], input)
+}
+
+/// Runs the command like [`run_command_with_input`], with the environment
+/// variables `envs` set.
+pub(crate) fn run_command_with_env(
+    cmd: &str,
+    args: &[&str],
+    envs: &[(&str, &str)],
+    input: &[u8],
+) -> Result<String> {
+    let mut child = Command::new(cmd)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // write from another thread so a full stdout pipe can't block the writer
+    let mut stdin = child.stdin.take().ok_or(anyhow!("Could not open stdin"))?;
+    let input = input.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| anyhow!("Could not write to stdin of {}", cmd))??;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", stderr);
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("The output of `{}` is not valid UTF-8", cmd))
 }
 
 pub(crate) fn find_executable(name: &str, error_msg: &str) -> Result<PathBuf> {
@@ -30,3 +76,28 @@ pub(crate) fn find_executable(name: &str, error_msg: &str) -> Result<PathBuf> {
 
     Ok(path)
 }
+
+/// Runs the command attached to the terminal, so the user can interact with it.
+/// If the command fails, returns an error.
+pub(crate) fn run_command_interactive(cmd: &str, args: &[&str]) -> Result<()> {
+    run_command_interactive_with_env(cmd, args, &[])
+}
+
+/// Runs the command like [`run_command_interactive`], with the environment
+/// variables `envs` set.
+pub(crate) fn run_command_interactive_with_env(
+    cmd: &str,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> Result<()> {
+    let status = Command::new(cmd)
+        .args(args)
+        .envs(envs.iter().copied())
+        .status()?;
+
+    if !status.success() {
+        bail!("`{}` exited with {}", cmd, status);
+    }
+
+    Ok(())
+}
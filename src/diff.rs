@@ -0,0 +1,589 @@
+use strum_macros::Display;
+
+use crate::util::SplitPrefixInclusive;
+
+/// How a file changed between the old and new side of a diff
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display)]
+pub(crate) enum FileStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+}
+
+/// A single `@@ -a,b +c,d @@` section of a file diff
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Hunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Text after the closing `@@`, usually the enclosing function
+    pub section: String,
+    /// Body lines, each starting with ` `, `+`, `-` or `\`
+    pub lines: Vec<String>,
+}
+
+impl Hunk {
+    pub(crate) fn added_lines(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| line.starts_with('+'))
+            .count()
+    }
+
+    pub(crate) fn removed_lines(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| line.starts_with('-'))
+            .count()
+    }
+}
+
+/// The diff of a single file, as found in the output of `git diff`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct FileDiff {
+    /// Path before the change, `None` for added files
+    pub old_path: Option<String>,
+    /// Path after the change, `None` for deleted files
+    pub new_path: Option<String>,
+    pub status: FileStatus,
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    /// Similarity index of renames and copies, in percent
+    pub similarity: Option<u8>,
+    pub is_binary: bool,
+    pub hunks: Vec<Hunk>,
+    /// The diff text of this file, as sent to the model
+    pub raw: String,
+}
+
+impl FileDiff {
+    /// Path of the file after the change, or before it for deleted files.
+    pub(crate) fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn added_lines(&self) -> usize {
+        self.hunks.iter().map(Hunk::added_lines).sum()
+    }
+
+    pub(crate) fn removed_lines(&self) -> usize {
+        self.hunks.iter().map(Hunk::removed_lines).sum()
+    }
+
+    /// Number of added and removed lines
+    pub(crate) fn lines_changed(&self) -> usize {
+        self.added_lines() + self.removed_lines()
+    }
+
+    /// Whether the file mode, e.g. the executable bit, changed.
+    pub(crate) fn is_mode_change(&self) -> bool {
+        matches!((&self.old_mode, &self.new_mode), (Some(old), Some(new)) if old != new)
+    }
+
+    /// Summarizes changes that say nothing beyond their metadata, such as
+    /// binary files, deletions, pure renames and mode changes, so they can be
+    /// described without asking the model.
+    pub(crate) fn deterministic_summary(&self) -> Option<String> {
+        let path = self.path();
+        let old_path = self.old_path.as_deref().unwrap_or(path);
+        if self.is_binary {
+            return Some(match self.status {
+                FileStatus::Added => format!("- Add binary asset {path}"),
+                FileStatus::Deleted => format!("- Delete binary asset {path}"),
+                FileStatus::Renamed => format!("- Rename binary asset {old_path} to {path}"),
+                FileStatus::Copied => format!("- Copy binary asset {old_path} to {path}"),
+                FileStatus::Modified => format!("- Update binary asset {path}"),
+            });
+        }
+        if self.status == FileStatus::Deleted {
+            return Some(format!("- Delete {path}"));
+        }
+        if !self.hunks.is_empty() {
+            return None;
+        }
+
+        let mode_change = self.describe_mode_change();
+        match (self.status, mode_change) {
+            (FileStatus::Added, _) => Some(format!("- Add empty file {path}")),
+            (FileStatus::Renamed, None) => Some(format!("- Rename {old_path} to {path}")),
+            (FileStatus::Renamed, Some(mode_change)) => {
+                Some(format!("- Rename {old_path} to {path}\n- {mode_change}"))
+            }
+            (FileStatus::Copied, _) => Some(format!("- Copy {old_path} to {path}")),
+            (_, Some(mode_change)) => Some(format!("- {mode_change}")),
+            _ => None,
+        }
+    }
+
+    fn describe_mode_change(&self) -> Option<String> {
+        if !self.is_mode_change() {
+            return None;
+        }
+        let path = self.path();
+        let old_mode = self.old_mode.as_deref().unwrap_or_default();
+        let new_mode = self.new_mode.as_deref().unwrap_or_default();
+        Some(match (old_mode, new_mode) {
+            ("100644", "100755") => format!("Make {path} executable"),
+            ("100755", "100644") => format!("Make {path} non-executable"),
+            (_, "120000") => format!("Replace {path} with a symlink"),
+            ("120000", _) => format!("Replace symlink {path} with a regular file"),
+            _ => format!("Change mode of {path} from {old_mode} to {new_mode}"),
+        })
+    }
+
+    /// Describes the change from its metadata and stats, without asking the model.
+    pub(crate) fn describe(&self) -> String {
+        if let Some(summary) = self.deterministic_summary() {
+            return summary;
+        }
+        let path = self.path();
+        match self.status {
+            FileStatus::Added => format!("- Add {path} ({} lines)", self.added_lines()),
+            _ => format!(
+                "- Update {path} (+{} -{} lines)",
+                self.added_lines(),
+                self.removed_lines()
+            ),
+        }
+    }
+}
+
+/// Parses the output of `git diff` into one [`FileDiff`] per file.
+///
+/// Anything before the first `diff --git` line, such as the commit header
+/// printed by `git show`, is ignored. The parser expects the format described
+/// in the Git documentation: https://git-scm.com/docs/git-diff
+pub(crate) fn parse(diff: &str) -> Vec<FileDiff> {
+    diff.split_prefix_inclusive("\ndiff --git ")
+        .into_iter()
+        .filter_map(|file_diff| parse_file_diff(file_diff.trim_start_matches('\n')))
+        .collect()
+}
+
+fn parse_file_diff(file_diff: &str) -> Option<FileDiff> {
+    let mut lines = file_diff.lines();
+    let (old_header_path, new_header_path) =
+        parse_header_paths(lines.next()?.strip_prefix("diff --git ")?)?;
+    // paths carry an `a/` and `b/` style prefix unless `diff.noprefix` is set
+    let has_prefix = has_diff_prefix(&old_header_path) && has_diff_prefix(&new_header_path);
+    let strip = |path: String| {
+        if has_prefix && has_diff_prefix(&path) {
+            path[2..].to_string()
+        } else {
+            path
+        }
+    };
+
+    let mut file = FileDiff {
+        old_path: Some(strip(old_header_path)),
+        new_path: Some(strip(new_header_path)),
+        status: FileStatus::Modified,
+        old_mode: None,
+        new_mode: None,
+        similarity: None,
+        is_binary: false,
+        hunks: Vec::new(),
+        raw: file_diff.to_string(),
+    };
+    // lines left in the current hunk on the old and new side
+    let mut remaining = (0, 0);
+
+    for line in lines {
+        if remaining != (0, 0) || line.starts_with('\\') {
+            if let Some(hunk) = file.hunks.last_mut() {
+                match line.chars().next() {
+                    Some('+') => remaining.1 -= remaining.1.min(1),
+                    Some('-') => remaining.0 -= remaining.0.min(1),
+                    Some('\\') => {}
+                    _ => {
+                        remaining.0 -= remaining.0.min(1);
+                        remaining.1 -= remaining.1.min(1);
+                    }
+                }
+                hunk.lines.push(line.to_string());
+                continue;
+            }
+        }
+
+        if let Some(hunk) = parse_hunk_header(line) {
+            remaining = (hunk.old_lines, hunk.new_lines);
+            file.hunks.push(hunk);
+        } else if let Some(mode) = line.strip_prefix("old mode ") {
+            file.old_mode = Some(mode.to_string());
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            file.new_mode = Some(mode.to_string());
+        } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+            file.status = FileStatus::Deleted;
+            file.old_mode = Some(mode.to_string());
+            file.new_path = None;
+        } else if let Some(mode) = line.strip_prefix("new file mode ") {
+            file.status = FileStatus::Added;
+            file.new_mode = Some(mode.to_string());
+            file.old_path = None;
+        } else if let Some(path) = line
+            .strip_prefix("rename from ")
+            .or_else(|| line.strip_prefix("rename old "))
+        {
+            file.status = FileStatus::Renamed;
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line
+            .strip_prefix("rename to ")
+            .or_else(|| line.strip_prefix("rename new "))
+        {
+            file.status = FileStatus::Renamed;
+            file.new_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            file.status = FileStatus::Copied;
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            file.status = FileStatus::Copied;
+            file.new_path = Some(unquote_path(path));
+        } else if let Some(similarity) = line.strip_prefix("similarity index ") {
+            file.similarity = similarity.trim_end_matches('%').parse().ok();
+        } else if let Some(index) = line.strip_prefix("index ") {
+            // `index abc..def 100644` repeats the mode when it did not change
+            if let Some((_, mode)) = index.split_once(' ') {
+                file.old_mode.get_or_insert_with(|| mode.to_string());
+                file.new_mode.get_or_insert_with(|| mode.to_string());
+            }
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            if file.status != FileStatus::Added {
+                file.old_path = parse_marker_path(path).map(strip);
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            if file.status != FileStatus::Deleted {
+                file.new_path = parse_marker_path(path).map(strip);
+            }
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.is_binary = true;
+        }
+    }
+
+    Some(file)
+}
+
+/// Whether a path from a `diff --git`, `---` or `+++` line starts with one
+/// of the prefixes git uses for the two sides of a diff.
+fn has_diff_prefix(path: &str) -> bool {
+    matches!(
+        path.as_bytes(),
+        [
+            b'a' | b'b' | b'c' | b'i' | b'o' | b'w' | b'1' | b'2',
+            b'/',
+            ..
+        ]
+    )
+}
+
+/// Splits the paths of a `diff --git a/old b/new` header.
+///
+/// Unquoted paths with spaces are ambiguous, so they are split where both
+/// sides name the same file. Renames are resolved later by the
+/// `rename from` and `rename to` lines.
+fn parse_header_paths(header: &str) -> Option<(String, String)> {
+    if let Some(quoted) = header.strip_prefix('"') {
+        let (old, rest) = split_quoted(quoted)?;
+        let new = rest.strip_prefix(' ')?;
+        return Some((old, unquote_path(new)));
+    }
+    if let Some((old, new)) = header.rsplit_once(" \"") {
+        let (new, _) = split_quoted(new)?;
+        return Some((old.to_string(), new));
+    }
+
+    if header.len() % 2 == 1 {
+        let middle = header.len() / 2;
+        if header.is_char_boundary(middle) && header.as_bytes()[middle] == b' ' {
+            let (old, new) = (&header[..middle], &header[middle + 1..]);
+            if old.get(2..) == new.get(2..) || old == new {
+                return Some((old.to_string(), new.to_string()));
+            }
+        }
+    }
+    let (old, new) = header
+        .rsplit_once(" b/")
+        .map(|(old, new)| (old.to_string(), format!("b/{new}")))
+        .or_else(|| {
+            header
+                .split_once(' ')
+                .map(|(old, new)| (old.to_string(), new.to_string()))
+        })?;
+    Some((old, new))
+}
+
+/// Parses the path of a `---` or `+++` line, `None` for `/dev/null`.
+fn parse_marker_path(path: &str) -> Option<String> {
+    // git appends a tab to paths containing spaces
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        None
+    } else {
+        Some(unquote_path(path))
+    }
+}
+
+fn unquote_path(path: &str) -> String {
+    match path.strip_prefix('"').and_then(split_quoted) {
+        Some((unquoted, _)) => unquoted,
+        None => path.to_string(),
+    }
+}
+
+/// Quotes a path the way git does with `core.quotePath` enabled: paths with
+/// control characters, quotes, backslashes or non-ASCII bytes are C-quoted.
+pub(crate) fn quote_path(path: &str) -> String {
+    let needs_quoting = path
+        .bytes()
+        .any(|b| !(0x20..0x7f).contains(&b) || b == b'"' || b == b'\\');
+    if !needs_quoting {
+        return path.to_string();
+    }
+
+    let mut quoted = String::with_capacity(path.len() + 2);
+    quoted.push('"');
+    for b in path.bytes() {
+        match b {
+            b'\n' => quoted.push_str("\\n"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\r' => quoted.push_str("\\r"),
+            0x07 => quoted.push_str("\\a"),
+            0x08 => quoted.push_str("\\b"),
+            0x0c => quoted.push_str("\\f"),
+            0x0b => quoted.push_str("\\v"),
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b if !(0x20..0x7f).contains(&b) => quoted.push_str(&format!("\\{b:03o}")),
+            b => quoted.push(b as char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads a C-style quoted string up to its closing quote, returning the
+/// unescaped string and the rest of the input.
+fn split_quoted(quoted: &str) -> Option<(String, &str)> {
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => {
+                return Some((
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                    &quoted[idx + 1..],
+                ))
+            }
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                match escaped {
+                    'n' => bytes.push(b'\n'),
+                    't' => bytes.push(b'\t'),
+                    'r' => bytes.push(b'\r'),
+                    'a' => bytes.push(0x07),
+                    'b' => bytes.push(0x08),
+                    'f' => bytes.push(0x0c),
+                    'v' => bytes.push(0x0b),
+                    '0'..='7' => {
+                        // three digit octal escape of a raw byte, used for non-ASCII paths
+                        let mut value = escaped.to_digit(8)?;
+                        for _ in 0..2 {
+                            let (_, digit) = chars.next()?;
+                            value = value * 8 + digit.to_digit(8)?;
+                        }
+                        bytes.push(u8::try_from(value).ok()?);
+                    }
+                    other => {
+                        let mut buf = [0; 4];
+                        bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+                    }
+                }
+            }
+            other => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    None
+}
+
+/// Parses a `@@ -a,b +c,d @@ section` line into an empty hunk.
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = parse_range(old)?;
+    let (new_start, new_lines) = parse_range(new)?;
+
+    Some(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        section: section.trim().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(diff: &str) -> FileDiff {
+        let mut files = parse(diff);
+        assert_eq!(files.len(), 1);
+        files.remove(0)
+    }
+
+    #[test]
+    fn test_parse_example() {
+        let files = parse(include_str!("../tests/data/example_1.diff"));
+        let paths = files.iter().map(FileDiff::path).collect::<Vec<&str>>();
+        assert_eq!(paths, [".vscode/launch.json", "foo", "src/main.rs"]);
+        assert_eq!(files[0].status, FileStatus::Added);
+        assert_eq!(files[0].added_lines(), 10);
+        assert_eq!(files[2].status, FileStatus::Modified);
+        assert_eq!(files[2].hunks.len(), 2);
+        assert_eq!(files[2].hunks[1].old_start, 19);
+        assert_eq!(files[2].hunks[1].new_lines, 28);
+        assert!(files.iter().all(|file| file.raw.starts_with("diff --git ")));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse(""), vec![]);
+        assert_eq!(parse("asdasdas"), vec![]);
+        assert_eq!(parse("diff --git a/"), vec![]);
+    }
+
+    #[test]
+    fn test_parse_new_file() {
+        let file = parse_one(
+            &r#"
+diff --git a/foo b/foo
+new file mode 100644
+index 0000000..a51b2a6
+--- /dev/null
++++ b/foo
+@@ -0,0 +1 @@
++sadasdas
+"#[1..],
+        );
+        assert_eq!(file.status, FileStatus::Added);
+        assert_eq!(file.old_path, None);
+        assert_eq!(file.new_path.as_deref(), Some("foo"));
+        assert_eq!(file.new_mode.as_deref(), Some("100644"));
+        assert_eq!(file.describe(), "- Add foo (1 lines)");
+    }
+
+    #[test]
+    fn test_parse_rename() {
+        let file = parse_one(
+            "diff --git a/old name.rs b/new name.rs\nsimilarity index 90%\nrename from old name.rs\nrename to new name.rs\n",
+        );
+        assert_eq!(file.status, FileStatus::Renamed);
+        assert_eq!(file.old_path.as_deref(), Some("old name.rs"));
+        assert_eq!(file.new_path.as_deref(), Some("new name.rs"));
+        assert_eq!(file.similarity, Some(90));
+        assert_eq!(
+            file.deterministic_summary().as_deref(),
+            Some("- Rename old name.rs to new name.rs")
+        );
+    }
+
+    #[test]
+    fn test_parse_old_and_new_name() {
+        let file = parse_one("diff --git a/old_name b/new_name\n--- a/old_name\n+++ b/new_name");
+        assert_eq!(file.old_path.as_deref(), Some("old_name"));
+        assert_eq!(file.new_path.as_deref(), Some("new_name"));
+    }
+
+    #[test]
+    fn test_parse_marker_path_without_prefix() {
+        let file = parse_one("diff --git a/aé b/aé\n--- aé\n+++ aé");
+        assert_eq!(file.old_path.as_deref(), Some("aé"));
+        assert_eq!(file.new_path.as_deref(), Some("aé"));
+    }
+
+    #[test]
+    fn test_parse_paths_with_spaces_and_quotes() {
+        let file =
+            parse_one("diff --git a/my file.txt b/my file.txt\nold mode 100644\nnew mode 100755\n");
+        assert_eq!(file.path(), "my file.txt");
+        assert!(file.is_mode_change());
+        assert_eq!(
+            file.deterministic_summary().as_deref(),
+            Some("- Make my file.txt executable")
+        );
+
+        let file = parse_one(
+            "diff --git \"a/caf\\303\\251 \\\"menu\\\".md\" \"b/caf\\303\\251 \\\"menu\\\".md\"\ndeleted file mode 100644\n",
+        );
+        assert_eq!(file.status, FileStatus::Deleted);
+        assert_eq!(file.path(), "café \"menu\".md");
+        assert_eq!(
+            file.deterministic_summary().as_deref(),
+            Some("- Delete café \"menu\".md")
+        );
+    }
+
+    #[test]
+    fn test_parse_no_prefix() {
+        let file = parse_one(
+            "diff --git src/main.rs src/main.rs\nindex 1..2 100644\n--- src/main.rs\n+++ src/main.rs\n@@ -1 +1 @@\n-a\n+b\n",
+        );
+        assert_eq!(file.path(), "src/main.rs");
+        assert!(!file.is_mode_change());
+        assert_eq!(file.deterministic_summary(), None);
+        assert_eq!(file.describe(), "- Update src/main.rs (+1 -1 lines)");
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        let file = parse_one(
+            "diff --git a/logo.png b/logo.png\nindex 1..2 100644\nBinary files a/logo.png and b/logo.png differ\n",
+        );
+        assert!(file.is_binary);
+        assert_eq!(file.lines_changed(), 0);
+        assert_eq!(
+            file.deterministic_summary().as_deref(),
+            Some("- Update binary asset logo.png")
+        );
+    }
+
+    #[test]
+    fn test_parse_hunk_lines_like_headers() {
+        let file = parse_one(
+            "diff --git a/a.md b/a.md\n--- a/a.md\n+++ b/a.md\n@@ -1 +1 @@ intro\n--- removed rule\n+++ added rule\n\\ No newline at end of file\n",
+        );
+        assert_eq!(file.path(), "a.md");
+        assert_eq!(file.hunks.len(), 1);
+        assert_eq!(file.hunks[0].section, "intro");
+        assert_eq!(file.hunks[0].lines.len(), 3);
+        assert_eq!(file.added_lines(), 1);
+        assert_eq!(file.removed_lines(), 1);
+    }
+
+    #[test]
+    fn test_quote_path() {
+        assert_eq!(quote_path("src/main.rs"), "src/main.rs");
+        assert_eq!(quote_path("docs/my file.md"), "docs/my file.md");
+        assert_eq!(quote_path("say \"hi\".txt"), "\"say \\\"hi\\\".txt\"");
+        assert_eq!(quote_path("café.txt"), "\"caf\\303\\251.txt\"");
+        for path in ["tab\tname", "caf\u{e9}.txt", "back\\slash"] {
+            assert_eq!(unquote_path(&quote_path(path)), path);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+
+use chardetng::EncodingDetector;
+
+/// Decodes `bytes` as text. Content that is not valid UTF-8 is decoded with a
+/// guessed legacy encoding, such as windows-1252 or Shift_JIS. Returns `None` when
+/// the bytes don't look like text in any encoding.
+pub(crate) fn decode(bytes: &[u8]) -> Option<Cow<'_, str>> {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some(Cow::Borrowed(text));
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let (encoding, confident) = detector.guess_assess(None, false);
+    if !confident {
+        return None;
+    }
+    let text = encoding.decode_without_bom_handling_and_without_replacement(bytes)?;
+    // single-byte encodings decode almost anything, so reject control characters
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+    {
+        return None;
+    }
+    debug!("Decoded non UTF-8 content as {}", encoding.name());
+    Some(text)
+}
+
+/// Decodes the output of `git diff` one file at a time. The diff of a file that
+/// can't be decoded is replaced by a `Binary files ... differ` line, so it is
+/// summarized like any other binary file.
+pub(crate) fn decode_diff(diff: &[u8]) -> String {
+    let mut output = String::new();
+    let mut start = 0;
+    while start < diff.len() {
+        let end = diff[start + 1..]
+            .windows(12)
+            .position(|window| window == b"\ndiff --git ")
+            .map_or(diff.len(), |pos| start + 1 + pos + 1);
+        let file_diff = &diff[start..end];
+        match decode(file_diff) {
+            Some(text) => output.push_str(&text),
+            None => output.push_str(&as_binary(file_diff)),
+        }
+        start = end;
+    }
+    output
+}
+
+/// Keeps the extended header of a file diff, replacing its hunks with the
+/// line git prints for binary files.
+fn as_binary(file_diff: &[u8]) -> String {
+    let mut header = String::new();
+    let mut labels = (None, None);
+    for line in file_diff.split_inclusive(|&b| b == b'\n') {
+        if let Some(label) = line.strip_prefix(b"--- ") {
+            labels.0 = Some(label_text(label));
+        } else if let Some(label) = line.strip_prefix(b"+++ ") {
+            labels.1 = Some(label_text(label));
+        } else if line.starts_with(b"@@ ") {
+            break;
+        } else {
+            header.push_str(&String::from_utf8_lossy(line));
+        }
+    }
+
+    match labels {
+        (Some(old_label), Some(new_label)) => {
+            warn!("Could not decode the diff of {new_label}, treating it as a binary file");
+            format!("{header}Binary files {old_label} and {new_label} differ\n")
+        }
+        _ => String::from_utf8_lossy(file_diff).into_owned(),
+    }
+}
+
+fn label_text(label: &[u8]) -> String {
+    String::from_utf8_lossy(label)
+        .trim_end_matches(['\n', '\r', '\t'])
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"plain text\n").as_deref(), Some("plain text\n"));
+        // "café au lait" saved as windows-1252
+        assert_eq!(
+            decode(b"// caf\xe9 au lait, cr\xe8me br\xfbl\xe9e et g\xe2teau\n").as_deref(),
+            Some("// café au lait, crème brûlée et gâteau\n")
+        );
+        assert_eq!(decode(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), None);
+        assert_eq!(decode(b"\x01\x02\x03\xff\xfe\x04"), None);
+    }
+
+    #[test]
+    fn test_decode_diff() {
+        let utf8 = "diff --git a/a.txt b/a.txt\nindex 1..2 100644\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\n+b\n";
+        let latin1: &[u8] = b"diff --git a/b.c b/b.c\nindex 3..4 100644\n--- a/b.c\n+++ b/b.c\n@@ -1 +1 @@\n-/* d\xe9but */\n+/* fin de l'\xe9t\xe9 */\n";
+        let garbage: &[u8] = b"diff --git a/c.dat b/c.dat\nnew file mode 100644\nindex 0..5\n--- /dev/null\n+++ b/c.dat\n@@ -0,0 +1 @@\n+\x01\x02\xff\xfe\x03\n";
+
+        let diff = [utf8.as_bytes(), latin1, garbage].concat();
+        let decoded = decode_diff(&diff);
+        assert!(decoded.starts_with(utf8));
+
+        let files = crate::diff::parse(&decoded);
+        assert_eq!(files.len(), 3);
+        assert!(files[1].raw.contains("+/* fin de l'été */"));
+        assert!(!files[1].is_binary);
+        assert_eq!(
+            files[2].raw,
+            "diff --git a/c.dat b/c.dat\nnew file mode 100644\nindex 0..5\nBinary files /dev/null and b/c.dat differ\n"
+        );
+        assert!(files[2].is_binary);
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use anyhow::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Name of the ignore file read from the repository root
+pub(crate) static GPTCOMMIT_IGNORE_FILE: &str = ".gptcommitignore";
+
+/// Decides which files are left out of summarization.
+///
+/// Patterns follow gitignore semantics: globs, `**`, anchoring with a leading
+/// or inner `/`, directory-only patterns with a trailing `/` and negation with
+/// a leading `!`. Rules from `.gptcommitignore` are applied after the
+/// `file_ignore` setting, so the repository can re-include files with `!`.
+#[derive(Debug, Clone)]
+pub(crate) struct FileIgnore {
+    matcher: Gitignore,
+}
+
+impl FileIgnore {
+    pub(crate) fn new(patterns: &[String], repo_root: Option<&Path>) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new("");
+        for pattern in patterns {
+            if let Err(err) = builder.add_line(None, pattern) {
+                warn!("Ignoring invalid file_ignore pattern {pattern:?}: {err}");
+            }
+        }
+
+        if let Some(repo_root) = repo_root {
+            let ignore_path = repo_root.join(GPTCOMMIT_IGNORE_FILE);
+            if ignore_path.is_file() {
+                debug!("Applying ignore rules from {}", ignore_path.display());
+                let contents = std::fs::read_to_string(&ignore_path)?;
+                for line in contents.lines() {
+                    if let Err(err) = builder.add_line(Some(ignore_path.clone()), line) {
+                        warn!(
+                            "Ignoring invalid pattern in {}: {err}",
+                            ignore_path.display()
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            matcher: builder.build()?,
+        })
+    }
+
+    /// Whether the file at `path`, relative to the repository root, is ignored.
+    pub(crate) fn is_ignored(&self, path: &str) -> bool {
+        self.matcher
+            .matched_path_or_any_parents(path.trim_start_matches('/'), false)
+            .is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_ignore(patterns: &[&str]) -> FileIgnore {
+        let patterns = patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        FileIgnore::new(&patterns, None).unwrap()
+    }
+
+    #[test]
+    fn test_directory_pattern() {
+        let file_ignore = file_ignore(&["dist/"]);
+        assert!(file_ignore.is_ignored("dist/app.js"));
+        assert!(file_ignore.is_ignored("web/dist/app.js"));
+        assert!(!file_ignore.is_ignored("redistribute.rs"));
+        assert!(!file_ignore.is_ignored("dist"));
+    }
+
+    #[test]
+    fn test_file_names_match_anywhere() {
+        let file_ignore = file_ignore(&["Cargo.lock", "*.snap"]);
+        assert!(file_ignore.is_ignored("Cargo.lock"));
+        assert!(file_ignore.is_ignored("crates/foo/Cargo.lock"));
+        assert!(file_ignore.is_ignored("tests/snapshots/a.snap"));
+        assert!(!file_ignore.is_ignored("Cargo.toml"));
+    }
+
+    #[test]
+    fn test_anchored_and_double_star_patterns() {
+        let file_ignore = file_ignore(&["/generated", "docs/**/*.png"]);
+        assert!(file_ignore.is_ignored("generated/api.rs"));
+        assert!(!file_ignore.is_ignored("src/generated/api.rs"));
+        assert!(file_ignore.is_ignored("docs/img/deep/logo.png"));
+        assert!(!file_ignore.is_ignored("src/logo.png"));
+    }
+
+    #[test]
+    fn test_negation() {
+        let file_ignore = file_ignore(&["*.lock", "!Cargo.lock"]);
+        assert!(file_ignore.is_ignored("yarn.lock"));
+        assert!(!file_ignore.is_ignored("Cargo.lock"));
+    }
+}
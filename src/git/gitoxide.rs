@@ -0,0 +1,979 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env,
+    hash::{Hash, Hasher},
+    ops::Range,
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Result};
+use gix::{
+    attrs::StateRef,
+    bstr::ByteSlice,
+    commit::describe::SelectRef,
+    diff::{
+        index::{Action, ChangeRef},
+        Rewrites,
+    },
+    index::entry::Mode,
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    },
+    revision::walk::Sorting,
+    status::tree_index::TrackRenames,
+    traverse::commit::simple::CommitTimeOrder,
+    worktree::stack::state::attributes::Source,
+    ObjectId, Repository,
+};
+use imara_diff::{Algorithm, Diff, InternedInput, Interner};
+
+use super::{CommitInfo, DiffBase, DiffOptions, GitClient};
+use crate::{
+    diff::quote_path,
+    encoding,
+    settings::{DiffAlgorithm, DiffWhitespace},
+};
+
+/// Lines of context around each change, as in `git diff`
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+/// Leading bytes searched for a NUL byte to tell binary files apart, as git does
+const BINARY_CHECK_BYTES: usize = 8000;
+
+/// Abbreviated length of the object ids on the `index` line
+const ABBREV_LEN: usize = 7;
+
+/// Longest function name shown in a hunk header, in bytes, as in git
+const FUNCTION_NAME_MAX_LEN: usize = 80;
+
+/// Reads the repository in-process with gitoxide, without spawning `git`.
+#[derive(Debug, Default)]
+pub(crate) struct GitoxideClient {}
+
+impl GitoxideClient {
+    fn open(&self) -> Result<Repository> {
+        Ok(gix::discover_with_environment_overrides(".")?)
+    }
+}
+
+/// Reads the index, honoring the temporary index git passes to hooks in
+/// `GIT_INDEX_FILE` for `git commit -a` and `git commit <paths>`.
+fn read_index(repo: &Repository) -> Result<gix::index::File> {
+    match env::var_os("GIT_INDEX_FILE") {
+        Some(path) => Ok(gix::index::File::at(
+            path,
+            repo.object_hash(),
+            false,
+            Default::default(),
+        )?),
+        None => {
+            let index = repo.index_or_empty()?;
+            Ok(gix::index::File::clone(&index))
+        }
+    }
+}
+
+/// Returns the tree of the commit `rev` points to
+fn commit_tree(repo: &Repository, rev: &str) -> Result<ObjectId> {
+    let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+    Ok(commit.tree_id()?.detach())
+}
+
+/// Returns the tree of the first parent of `rev`, or the empty tree for a root
+/// commit
+fn parent_tree(repo: &Repository, rev: &str) -> Result<ObjectId> {
+    let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+    let parent = commit.parent_ids().next();
+    match parent {
+        Some(parent) => Ok(parent.object()?.peel_to_commit()?.tree_id()?.detach()),
+        None => Ok(ObjectId::empty_tree(repo.object_hash())),
+    }
+}
+
+/// Returns the changes of the index, or of the tip of a commit range, against
+/// `base`, limited to `pathspec` and sorted by path
+fn get_changes(
+    repo: &Repository,
+    base: &DiffBase,
+    pathspec: &[String],
+    rename_detection: bool,
+) -> Result<Vec<FileChange>> {
+    // a commit range is diffed through an index built from the tip's tree
+    let index = match base {
+        DiffBase::Range(_, tip) | DiffBase::Commit(tip) => {
+            repo.index_from_tree(&commit_tree(repo, tip)?)?
+        }
+        _ => read_index(repo)?,
+    };
+    let tree_id = match base {
+        DiffBase::Head => repo.head_tree_id_or_empty()?.detach(),
+        DiffBase::Range(base, _) => commit_tree(repo, base)?,
+        DiffBase::ParentOf(rev) | DiffBase::Commit(rev) => parent_tree(repo, rev)?,
+    };
+    // like `git diff`, an empty pathspec matches the whole repository, even
+    // when run from a subdirectory
+    let mut pathspec = repo.pathspec(
+        false,
+        pathspec.iter().map(String::as_str),
+        true,
+        &index,
+        Source::IdMapping,
+    )?;
+    let renames = if rename_detection {
+        TrackRenames::Given(Rewrites::default())
+    } else {
+        TrackRenames::Disabled
+    };
+
+    let mut changes = Vec::new();
+    repo.tree_index_status(
+        &tree_id,
+        &index,
+        Some(&mut pathspec),
+        renames,
+        |change, _, _| {
+            changes.push(FileChange::from(change));
+            Ok::<_, std::convert::Infallible>(Action::Continue)
+        },
+    )?;
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(changes)
+}
+
+/// Reads the id and the message of a commit
+fn commit_info(commit: &gix::Commit<'_>) -> CommitInfo {
+    CommitInfo {
+        id: commit.id.to_string(),
+        message: commit
+            .message_raw_sloppy()
+            .to_str_lossy()
+            .trim_end()
+            .to_string(),
+    }
+}
+
+impl GitClient for GitoxideClient {
+    fn get_repo_root(&self) -> Result<PathBuf> {
+        let repo = self.open()?;
+        let workdir = repo
+            .workdir()
+            .ok_or(anyhow!("The repository has no work tree"))?;
+        Ok(std::fs::canonicalize(workdir)?)
+    }
+
+    fn get_hooks_path(&self) -> Result<PathBuf> {
+        let repo = self.open()?;
+        if let Some(hooks_path) = repo.config_snapshot().trusted_path("core.hooksPath") {
+            return Ok(hooks_path?.into_owned());
+        }
+        Ok(repo.common_dir().join("hooks"))
+    }
+
+    fn get_git_dir(&self) -> Result<PathBuf> {
+        let repo = self.open()?;
+        Ok(repo.git_dir().to_path_buf())
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let repo = self.open()?;
+        let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+        Ok(commit.id.to_string())
+    }
+
+    fn get_merge_heads(&self) -> Result<Vec<String>> {
+        let repo = self.open()?;
+        let merge_head_path = repo.git_dir().join("MERGE_HEAD");
+        if !merge_head_path.is_file() {
+            return Ok(vec![]);
+        }
+        Ok(std::fs::read_to_string(merge_head_path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn get_commits(&self, base: &str, tip: &str) -> Result<Vec<CommitInfo>> {
+        let repo = self.open()?;
+        let base = repo.rev_parse_single(base)?.detach();
+        let tip = repo.rev_parse_single(tip)?.detach();
+        let walk = repo
+            .rev_walk([tip])
+            .with_hidden([base])
+            .sorting(Sorting::ByCommitTime(CommitTimeOrder::NewestFirst))
+            .all()?;
+
+        let mut commits = Vec::new();
+        for info in walk {
+            commits.push(commit_info(&info?.object()?));
+        }
+        Ok(commits)
+    }
+
+    fn get_commit(&self, rev: &str) -> Result<CommitInfo> {
+        let repo = self.open()?;
+        let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+        Ok(commit_info(&commit))
+    }
+
+    fn get_merge_base(&self, one: &str, two: &str) -> Result<String> {
+        let repo = self.open()?;
+        let one = repo.rev_parse_single(one)?.detach();
+        let two = repo.rev_parse_single(two)?.detach();
+        Ok(repo.merge_base(one, two)?.to_string())
+    }
+
+    fn get_parents(&self, rev: &str) -> Result<Vec<String>> {
+        let repo = self.open()?;
+        let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+        Ok(commit.parent_ids().map(|id| id.to_string()).collect())
+    }
+
+    fn reword_commit(&self, rev: &str, parent: &str, message: &str) -> Result<String> {
+        let repo = self.open()?;
+        let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+        let parent = repo.rev_parse_single(parent)?.detach();
+        let committer = repo
+            .committer()
+            .ok_or(anyhow!("No committer identity is configured"))??;
+        let copy = repo.new_commit_as(
+            committer,
+            commit.author()?,
+            message,
+            commit.tree_id()?,
+            [parent],
+        )?;
+        Ok(copy.id.to_string())
+    }
+
+    fn update_head(&self, new: &str, old: &str, reason: &str) -> Result<()> {
+        let repo = self.open()?;
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: reason.into(),
+                },
+                expected: PreviousValue::MustExistAndMatch(Target::Object(
+                    repo.rev_parse_single(old)?.detach(),
+                )),
+                new: Target::Object(repo.rev_parse_single(new)?.detach()),
+            },
+            name: "HEAD".try_into()?,
+            deref: true,
+        })?;
+        Ok(())
+    }
+
+    fn get_latest_tag(&self, rev: &str) -> Result<Option<String>> {
+        let repo = self.open()?;
+        let commit = repo.rev_parse_single(rev)?.object()?.peel_to_commit()?;
+        let resolution = commit.describe().names(SelectRef::AllTags).try_resolve()?;
+        Ok(resolution
+            .and_then(|resolution| resolution.outcome.name)
+            .map(|name| name.to_str_lossy().into_owned()))
+    }
+
+    fn get_remote_head(&self, remote: &str) -> Result<Option<String>> {
+        let repo = self.open()?;
+        let remote_head =
+            repo.try_find_reference(format!("refs/remotes/{remote}/HEAD").as_str())?;
+        Ok(remote_head.and_then(|reference| match reference.target() {
+            gix::refs::TargetRef::Symbolic(name) => Some(name.shorten().to_string()),
+            gix::refs::TargetRef::Object(_) => None,
+        }))
+    }
+
+    fn get_staged_diff(
+        &self,
+        base: &DiffBase,
+        options: &DiffOptions,
+        pathspec: &[String],
+    ) -> Result<String> {
+        let repo = self.open()?;
+        let changes = get_changes(&repo, base, pathspec, options.rename_detection)?;
+
+        let mut output = String::new();
+        for change in changes {
+            output.push_str(&change.render(&repo, options)?);
+        }
+        Ok(output)
+    }
+
+    fn get_changed_paths(&self, base: &DiffBase, pathspec: &[String]) -> Result<Vec<String>> {
+        let repo = self.open()?;
+        Ok(get_changes(&repo, base, pathspec, false)?
+            .iter()
+            .map(|change| change.path().to_string())
+            .collect())
+    }
+
+    fn get_attributes(
+        &self,
+        paths: &[&str],
+        attributes: &[&str],
+    ) -> Result<HashMap<String, HashMap<String, String>>> {
+        let repo = self.open()?;
+        let index = read_index(&repo)?;
+        let mut stack = repo.attributes_only(&index, Source::IdMapping)?;
+        let mut outcome = stack.selected_attribute_matches(attributes.iter().copied());
+
+        let mut attributes_for_path: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for path in paths {
+            outcome.reset();
+            stack
+                .at_entry(*path, None)?
+                .matching_attributes(&mut outcome);
+            for attribute in outcome.iter_selected() {
+                let value = match attribute.assignment.state {
+                    StateRef::Set => "set".to_string(),
+                    StateRef::Unset => "unset".to_string(),
+                    StateRef::Value(value) => value.as_bstr().to_str_lossy().into_owned(),
+                    StateRef::Unspecified => continue,
+                };
+                attributes_for_path
+                    .entry(path.to_string())
+                    .or_default()
+                    .insert(attribute.assignment.name.as_str().to_string(), value);
+            }
+        }
+        Ok(attributes_for_path)
+    }
+}
+
+/// One side of a staged change
+struct Entry {
+    path: String,
+    mode: Mode,
+    id: ObjectId,
+}
+
+/// A change between HEAD and the index, in the shape of a `git diff` file header
+struct FileChange {
+    old: Option<Entry>,
+    new: Option<Entry>,
+    /// Whether `old` and `new` are the source and destination of a rename or copy
+    rewrite: Option<Rewrite>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Rewrite {
+    Rename,
+    Copy,
+}
+
+impl From<ChangeRef<'_, '_>> for FileChange {
+    fn from(change: ChangeRef<'_, '_>) -> Self {
+        let entry = |path: &gix::bstr::BStr, mode: Mode, id: &gix::hash::oid| Entry {
+            path: path.to_str_lossy().into_owned(),
+            mode,
+            id: id.to_owned(),
+        };
+        match change {
+            ChangeRef::Addition {
+                location,
+                entry_mode,
+                id,
+                ..
+            } => Self {
+                old: None,
+                new: Some(entry(&location, entry_mode, &id)),
+                rewrite: None,
+            },
+            ChangeRef::Deletion {
+                location,
+                entry_mode,
+                id,
+                ..
+            } => Self {
+                old: Some(entry(&location, entry_mode, &id)),
+                new: None,
+                rewrite: None,
+            },
+            ChangeRef::Modification {
+                location,
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+                ..
+            } => Self {
+                old: Some(entry(&location, previous_entry_mode, &previous_id)),
+                new: Some(entry(&location, entry_mode, &id)),
+                rewrite: None,
+            },
+            ChangeRef::Rewrite {
+                source_location,
+                source_entry_mode,
+                source_id,
+                location,
+                entry_mode,
+                id,
+                copy,
+                ..
+            } => Self {
+                old: Some(entry(&source_location, source_entry_mode, &source_id)),
+                new: Some(entry(&location, entry_mode, &id)),
+                rewrite: Some(if copy { Rewrite::Copy } else { Rewrite::Rename }),
+            },
+        }
+    }
+}
+
+impl FileChange {
+    fn path(&self) -> &str {
+        self.new
+            .as_ref()
+            .or(self.old.as_ref())
+            .map(|entry| entry.path.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Renders the change like `git diff --staged`, or an empty string when
+    /// nothing is left to show, e.g. for whitespace-only changes that are ignored.
+    fn render(&self, repo: &Repository, options: &DiffOptions) -> Result<String> {
+        let old_path = self.old.as_ref().map_or(self.path(), |old| &old.path);
+        let new_path = self.new.as_ref().map_or(self.path(), |new| &new.path);
+        let mut header = format!(
+            "diff --git {} {}\n",
+            quote_path(&format!("a/{old_path}")),
+            quote_path(&format!("b/{new_path}"))
+        );
+
+        match (&self.old, &self.new) {
+            (None, Some(new)) => header.push_str(&format!("new file mode {}\n", mode(new.mode))),
+            (Some(old), None) => {
+                header.push_str(&format!("deleted file mode {}\n", mode(old.mode)))
+            }
+            (Some(old), Some(new)) if old.mode != new.mode => header.push_str(&format!(
+                "old mode {}\nnew mode {}\n",
+                mode(old.mode),
+                mode(new.mode)
+            )),
+            _ => {}
+        }
+
+        let old_data = self
+            .old
+            .as_ref()
+            .map(|old| read_blob(repo, old))
+            .transpose()?;
+        let new_data = self
+            .new
+            .as_ref()
+            .map(|new| read_blob(repo, new))
+            .transpose()?;
+        if let Some(rewrite) = self.rewrite {
+            let similarity = match (&self.old, &self.new) {
+                (Some(old), Some(new)) if old.id == new.id => 100,
+                _ => similarity(
+                    old_data.as_deref().unwrap_or_default(),
+                    new_data.as_deref().unwrap_or_default(),
+                ),
+            };
+            let verb = match rewrite {
+                Rewrite::Rename => "rename",
+                Rewrite::Copy => "copy",
+            };
+            header.push_str(&format!(
+                "similarity index {similarity}%\n{verb} from {}\n{verb} to {}\n",
+                quote_path(old_path),
+                quote_path(new_path)
+            ));
+        }
+
+        let old_id = self.old.as_ref().map(|old| old.id);
+        let new_id = self.new.as_ref().map(|new| new.id);
+        if old_id == new_id {
+            return Ok(if self.rewrite.is_some() || self.is_mode_change() {
+                header
+            } else {
+                String::new()
+            });
+        }
+        let null_id = ObjectId::null(repo.object_hash());
+        header.push_str(&format!(
+            "index {}..{}",
+            abbreviate(old_id.unwrap_or(null_id)),
+            abbreviate(new_id.unwrap_or(null_id))
+        ));
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) if old.mode == new.mode => {
+                header.push_str(&format!(" {}\n", mode(old.mode)))
+            }
+            _ => header.push('\n'),
+        }
+
+        let old_label = match self.old {
+            Some(_) => quote_path(&format!("a/{old_path}")),
+            None => "/dev/null".to_string(),
+        };
+        let new_label = match self.new {
+            Some(_) => quote_path(&format!("b/{new_path}")),
+            None => "/dev/null".to_string(),
+        };
+        let old_data = old_data.unwrap_or_default();
+        let new_data = new_data.unwrap_or_default();
+        let texts = if is_binary(&old_data) || is_binary(&new_data) {
+            None
+        } else {
+            encoding::decode(&old_data).zip(encoding::decode(&new_data))
+        };
+        let Some((old_text, new_text)) = texts else {
+            header.push_str(&format!(
+                "Binary files {old_label} and {new_label} differ\n"
+            ));
+            return Ok(header);
+        };
+
+        let hunks = unified_diff(&old_text, &new_text, options)?;
+        if hunks.is_empty() {
+            // whitespace-only changes leave nothing to show for modified files
+            return Ok(
+                if self.old.is_some() && self.new.is_some() && !self.is_mode_change() {
+                    String::new()
+                } else {
+                    header
+                },
+            );
+        }
+        // git ends names containing spaces with a tab to keep them unambiguous
+        let tab = |label: &str| if label.contains(' ') { "\t" } else { "" };
+        Ok(format!(
+            "{header}--- {old_label}{}\n+++ {new_label}{}\n{hunks}",
+            tab(&old_label),
+            tab(&new_label)
+        ))
+    }
+
+    fn is_mode_change(&self) -> bool {
+        matches!((&self.old, &self.new), (Some(old), Some(new)) if old.mode != new.mode)
+    }
+}
+
+fn mode(mode: Mode) -> String {
+    format!("{:06o}", mode.bits())
+}
+
+fn abbreviate(id: ObjectId) -> String {
+    id.to_hex_with_len(ABBREV_LEN).to_string()
+}
+
+/// Reads the content of an entry. Submodules are shown by their commit, as git does.
+fn read_blob(repo: &Repository, entry: &Entry) -> Result<Vec<u8>> {
+    if entry.mode == Mode::COMMIT {
+        return Ok(format!("Subproject commit {}\n", entry.id).into_bytes());
+    }
+    Ok(repo.find_object(entry.id)?.detach().data)
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_CHECK_BYTES)].contains(&0)
+}
+
+/// A line compared with the whitespace setting applied. It is shown as
+/// written, with its terminator, so a missing final newline is a change.
+#[derive(Clone, Copy)]
+struct Line<'a> {
+    text: &'a str,
+    whitespace: DiffWhitespace,
+}
+
+impl Line<'_> {
+    fn normalized(&self) -> Cow<'_, str> {
+        normalize_whitespace(self.text, self.whitespace)
+    }
+}
+
+impl PartialEq for Line<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for Line<'_> {}
+
+impl Hash for Line<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl AsRef<[u8]> for Line<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.text.as_bytes()
+    }
+}
+
+/// The line as compared by the diff, after applying the whitespace setting
+fn normalize_whitespace(line: &str, whitespace: DiffWhitespace) -> Cow<'_, str> {
+    match whitespace {
+        DiffWhitespace::Keep => Cow::Borrowed(line),
+        DiffWhitespace::IgnoreAtEol => Cow::Borrowed(line.trim_end()),
+        DiffWhitespace::IgnoreAll => line.chars().filter(|c| !c.is_whitespace()).collect(),
+        DiffWhitespace::IgnoreChange => {
+            let mut normalized = String::with_capacity(line.len());
+            let mut in_whitespace = false;
+            for c in line.chars() {
+                if c.is_whitespace() {
+                    in_whitespace = true;
+                } else {
+                    if in_whitespace {
+                        normalized.push(' ');
+                    }
+                    in_whitespace = false;
+                    normalized.push(c);
+                }
+            }
+            Cow::Owned(normalized)
+        }
+    }
+}
+
+fn interned_lines<'a>(
+    old: &'a str,
+    new: &'a str,
+    whitespace: DiffWhitespace,
+) -> InternedInput<Line<'a>> {
+    let lines = |text: &'a str| {
+        text.split_inclusive('\n')
+            .map(move |text| Line { text, whitespace })
+    };
+    let mut input = InternedInput {
+        before: Vec::new(),
+        after: Vec::new(),
+        interner: Interner::new(0),
+    };
+    input.update_before(lines(old));
+    input.update_after(lines(new));
+    input
+}
+
+/// Lines `before` of the old text replaced by lines `after` of the new one
+struct LineChange {
+    before: Range<isize>,
+    after: Range<isize>,
+}
+
+/// Renders the hunks of a unified diff between `old` and `new`, honoring the
+/// algorithm, whitespace, context and function context options. Changes are
+/// grouped into hunks and their headers written as git's xdiff does.
+fn unified_diff(old: &str, new: &str, options: &DiffOptions) -> Result<String> {
+    let input = interned_lines(old, new, options.whitespace);
+    // imara-diff has no patience implementation; histogram is its refinement
+    let algorithm = match options.algorithm {
+        DiffAlgorithm::Myers => Algorithm::Myers,
+        DiffAlgorithm::Minimal => Algorithm::MyersMinimal,
+        DiffAlgorithm::Patience | DiffAlgorithm::Histogram => Algorithm::Histogram,
+    };
+    let mut diff = Diff::compute(algorithm, &input);
+    // slide changes to where git puts them, with its indent heuristic
+    diff.postprocess_lines(&input);
+    let changes = diff
+        .hunks()
+        .map(|hunk| LineChange {
+            before: hunk.before.start as isize..hunk.before.end as isize,
+            after: hunk.after.start as isize..hunk.after.end as isize,
+        })
+        .collect::<Vec<LineChange>>();
+
+    let old = old.split_inclusive('\n').collect::<Vec<&str>>();
+    let new = new.split_inclusive('\n').collect::<Vec<&str>>();
+    let (old_len, new_len) = (old.len() as isize, new.len() as isize);
+    let context = options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES) as isize;
+    let mut output = String::new();
+    let mut next = 0;
+    while next < changes.len() {
+        let first = &changes[next];
+        // changes closer than twice the context share a hunk
+        let mut last = next;
+        while last + 1 < changes.len()
+            && changes[last + 1].before.start - changes[last].before.end <= 2 * context
+        {
+            last += 1;
+        }
+
+        let mut old_start = (first.before.start - context).max(0);
+        let mut new_start = (first.after.start - context).max(0);
+        if options.function_context {
+            let mut line = first.before.start;
+            // a change at the end only needs the function before it, unless it
+            // adds whole functions
+            let adds_functions = line >= old_len
+                && new[first.after.start as usize..]
+                    .iter()
+                    .any(|line| function_name(line).is_some());
+            if line >= old_len {
+                line = old_len - 1;
+            }
+            if !adds_functions {
+                let mut function_start = find_function_line(&old, line, -1).unwrap_or(-1);
+                while function_start > 0
+                    && !is_blank(old[function_start as usize - 1])
+                    && function_name(old[function_start as usize - 1]).is_none()
+                {
+                    function_start -= 1;
+                }
+                let function_start = function_start.max(0);
+                if function_start < old_start {
+                    new_start = (new_start - (old_start - function_start)).max(0);
+                    old_start = function_start;
+                }
+            }
+        }
+
+        let (old_end, new_end) = loop {
+            let change = &changes[last];
+            let trailing = context
+                .min(old_len - change.before.end)
+                .min(new_len - change.after.end);
+            let mut old_end = change.before.end + trailing;
+            let mut new_end = change.after.end + trailing;
+            if options.function_context {
+                let mut function_end =
+                    find_function_line(&old, change.before.end, old_len).unwrap_or(-1);
+                while function_end > 0 && is_blank(old[function_end as usize - 1]) {
+                    function_end -= 1;
+                }
+                if function_end < 0 {
+                    function_end = old_len;
+                }
+                if function_end > old_end {
+                    new_end = (new_end + function_end - old_end).min(new_len);
+                    old_end = function_end;
+                }
+                // the next change joins the hunk when it is in the same function
+                if let Some(next_change) = changes.get(last + 1) {
+                    let line = next_change.before.start.min(old_len - 1);
+                    if line - context <= old_end
+                        || find_function_line(&old, line, old_end).is_none()
+                    {
+                        last += 1;
+                        continue;
+                    }
+                }
+            }
+            break (old_end, new_end);
+        };
+
+        // the section heading is the closest function line above the hunk
+        let heading = find_function_line(&old, old_start - 1, -1)
+            .and_then(|line| function_name(old[line as usize]))
+            .filter(|name| !name.is_empty())
+            .map(|name| format!(" {name}"))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "@@ -{} +{} @@{heading}\n",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start)
+        ));
+
+        for line in new_start..changes[next].after.start {
+            push_line(&mut output, ' ', new[line as usize]);
+        }
+        let mut old_line = changes[next].before.start;
+        let mut new_line = changes[next].after.start;
+        for change in &changes[next..=last] {
+            while new_line < change.after.start && old_line < change.before.start {
+                push_line(&mut output, ' ', new[new_line as usize]);
+                new_line += 1;
+                old_line += 1;
+            }
+            for line in change.before.clone() {
+                push_line(&mut output, '-', old[line as usize]);
+            }
+            for line in change.after.clone() {
+                push_line(&mut output, '+', new[line as usize]);
+            }
+            old_line = change.before.end;
+            new_line = change.after.end;
+        }
+        for line in new_line..new_end {
+            push_line(&mut output, ' ', new[line as usize]);
+        }
+        next = last + 1;
+    }
+    Ok(output)
+}
+
+/// Formats the start and length of one side of a hunk, where an empty side
+/// starts at the line before it and a length of one is left out
+fn hunk_range(start: isize, len: isize) -> String {
+    let start = if len == 0 { start } else { start + 1 };
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{start},{len}")
+    }
+}
+
+fn push_line(output: &mut String, prefix: char, line: &str) {
+    output.push(prefix);
+    output.push_str(line);
+    if !line.ends_with('\n') {
+        output.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// The function a line starts, following git's default funcname rule: lines
+/// starting with a letter, `_` or `$` start a function. The name is cut to
+/// git's length, without trailing whitespace.
+fn function_name(line: &str) -> Option<&str> {
+    let first = line.bytes().next()?;
+    if !(first.is_ascii_alphabetic() || first == b'_' || first == b'$') {
+        return None;
+    }
+    let mut end = line.len().min(FUNCTION_NAME_MAX_LEN);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(line[..end].trim_end())
+}
+
+/// Searches from `start` towards `limit`, excluded, for the line starting a
+/// function
+fn find_function_line(lines: &[&str], start: isize, limit: isize) -> Option<isize> {
+    let step = if start > limit { -1 } else { 1 };
+    let mut line = start;
+    while line != limit && 0 <= line && line < lines.len() as isize {
+        if function_name(lines[line as usize]).is_some() {
+            return Some(line);
+        }
+        line += step;
+    }
+    None
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Similarity index of a rename or copy in percent: the share of the longer
+/// side made of lines kept unchanged
+fn similarity(old: &[u8], new: &[u8]) -> u8 {
+    let old = String::from_utf8_lossy(old);
+    let new = String::from_utf8_lossy(new);
+    let input = interned_lines(&old, &new, DiffWhitespace::Keep);
+    let max_len = input.before.len().max(input.after.len());
+    if max_len == 0 {
+        return 100;
+    }
+    let diff = Diff::compute(Algorithm::Histogram, &input);
+    let kept = input.before.len() - diff.count_removals() as usize;
+    (kept * 100 / max_len) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keep_whitespace() -> DiffOptions {
+        DiffOptions {
+            algorithm: DiffAlgorithm::Minimal,
+            whitespace: DiffWhitespace::Keep,
+            context_lines: None,
+            function_context: false,
+            rename_detection: true,
+        }
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            unified_diff(old, new, &keep_whitespace()).unwrap(),
+            "@@ -2,9 +2,10 @@ a\n b\n c\n d\n-e\n+E\n f\n g\n h\n i\n j\n+k\n"
+        );
+        let options = DiffOptions {
+            context_lines: Some(1),
+            ..keep_whitespace()
+        };
+        assert_eq!(
+            unified_diff(old, new, &options).unwrap(),
+            "@@ -4,3 +4,3 @@ c\n d\n-e\n+E\n f\n@@ -10 +10,2 @@ i\n j\n+k\n"
+        );
+        assert_eq!(unified_diff(old, old, &keep_whitespace()).unwrap(), "");
+    }
+
+    #[test]
+    fn test_unified_diff_added_and_deleted_files() {
+        assert_eq!(
+            unified_diff("", "one\ntwo\n", &keep_whitespace()).unwrap(),
+            "@@ -0,0 +1,2 @@\n+one\n+two\n"
+        );
+        assert_eq!(
+            unified_diff("one\n", "", &keep_whitespace()).unwrap(),
+            "@@ -1 +0,0 @@\n-one\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_missing_newline() {
+        assert_eq!(
+            unified_diff("one\ntwo", "one\ntwo\n", &keep_whitespace()).unwrap(),
+            "@@ -1,2 +1,2 @@\n one\n-two\n\\ No newline at end of file\n+two\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_function_context() {
+        let old = "fn one() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n    let d = 4;\n    run(a);\n}\n\nfn two() {\n    let x = 1;\n    run(x);\n}\n";
+        let options = DiffOptions {
+            context_lines: Some(1),
+            ..keep_whitespace()
+        };
+        assert_eq!(
+            unified_diff(old, &old.replace("run(a)", "walk(a)"), &options).unwrap(),
+            "@@ -5,3 +5,3 @@ fn one() {\n     let d = 4;\n-    run(a);\n+    walk(a);\n }\n"
+        );
+        assert_eq!(
+            unified_diff(old, &old.replace("run(x)", "walk(x)"), &options).unwrap(),
+            "@@ -10,3 +10,3 @@ fn two() {\n     let x = 1;\n-    run(x);\n+    walk(x);\n }\n"
+        );
+        let options = DiffOptions {
+            function_context: true,
+            ..options
+        };
+        assert_eq!(
+            unified_diff(old, &old.replace("run(a)", "walk(a)"), &options).unwrap(),
+            "@@ -1,7 +1,7 @@\n fn one() {\n     let a = 1;\n     let b = 2;\n     let c = 3;\n     let d = 4;\n-    run(a);\n+    walk(a);\n }\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_whitespace() {
+        let old = "fn main() {\n    let x = 1;\n}\n";
+        let new = "fn main() {\n  let  x = 1;  \n}\n";
+        assert_eq!(
+            unified_diff(old, new, &keep_whitespace())
+                .unwrap()
+                .lines()
+                .count(),
+            5
+        );
+        for whitespace in [DiffWhitespace::IgnoreAll, DiffWhitespace::IgnoreChange] {
+            let options = DiffOptions {
+                whitespace,
+                ..keep_whitespace()
+            };
+            assert_eq!(unified_diff(old, new, &options).unwrap(), "");
+        }
+        let options = DiffOptions {
+            whitespace: DiffWhitespace::IgnoreAtEol,
+            ..keep_whitespace()
+        };
+        assert_ne!(unified_diff(old, new, &options).unwrap(), "");
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity(b"a\nb\nc\nd\n", b"a\nb\nc\nd\n"), 100);
+        assert_eq!(similarity(b"a\nb\nc\nd\n", b"a\nb\nc\nx\n"), 75);
+        assert_eq!(similarity(b"a\n", b"b\n"), 0);
+    }
+}
@@ -0,0 +1,429 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs::{self},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+
+#[cfg(unix)]
+use std::{fs::Permissions, os::unix::prelude::PermissionsExt};
+
+use crate::{
+    diff,
+    settings::{DiffAlgorithm, DiffOverride, DiffSettings, DiffWhitespace, GitBackend, Settings},
+};
+use anyhow::{bail, Result};
+use ignore::gitignore::GitignoreBuilder;
+
+pub(crate) mod gitoxide;
+pub(crate) mod subprocess;
+
+use gitoxide::GitoxideClient;
+use subprocess::SubprocessClient;
+
+/// Remotes whose default branch pull requests usually target, in order of preference
+const REMOTES: [&str; 2] = ["upstream", "origin"];
+
+/// Options of a single `git diff` run, resolved from the `diff` settings
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DiffOptions {
+    pub algorithm: DiffAlgorithm,
+    pub whitespace: DiffWhitespace,
+    pub context_lines: Option<u32>,
+    pub function_context: bool,
+    pub rename_detection: bool,
+}
+
+impl DiffOptions {
+    fn new(settings: &DiffSettings) -> Self {
+        Self {
+            algorithm: DiffAlgorithm::from_str(settings.algorithm.as_deref().unwrap_or_default())
+                .unwrap_or_default(),
+            whitespace: DiffWhitespace::from_str(
+                settings.whitespace.as_deref().unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            context_lines: settings.context_lines,
+            function_context: settings.function_context.unwrap_or(true),
+            rename_detection: settings.rename_detection.unwrap_or(true),
+        }
+    }
+
+    fn with_override(&self, diff_override: &DiffOverride) -> Self {
+        let mut options = self.clone();
+        if let Some(algorithm) = &diff_override.algorithm {
+            options.algorithm = DiffAlgorithm::from_str(algorithm).unwrap_or(self.algorithm);
+        }
+        if let Some(whitespace) = &diff_override.whitespace {
+            options.whitespace = DiffWhitespace::from_str(whitespace).unwrap_or(self.whitespace);
+        }
+        if diff_override.context_lines.is_some() {
+            options.context_lines = diff_override.context_lines;
+        }
+        if let Some(function_context) = diff_override.function_context {
+            options.function_context = function_context;
+        }
+        options
+    }
+
+    fn to_args(&self) -> Vec<String> {
+        let mut args = vec![format!("--diff-algorithm={}", self.algorithm)];
+        match self.whitespace {
+            DiffWhitespace::IgnoreAll => args.push("--ignore-all-space".to_string()),
+            DiffWhitespace::IgnoreChange => args.push("--ignore-space-change".to_string()),
+            DiffWhitespace::IgnoreAtEol => args.push("--ignore-space-at-eol".to_string()),
+            DiffWhitespace::Keep => {}
+        }
+        if let Some(context_lines) = self.context_lines {
+            args.push(format!("--unified={context_lines}"));
+        }
+        if self.function_context {
+            args.push("--function-context".to_string());
+        }
+        args.push(if self.rename_detection {
+            "--find-renames".to_string()
+        } else {
+            "--no-renames".to_string()
+        });
+        args
+    }
+}
+
+/// The commit whose tree the index, or another commit, is diffed against
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) enum DiffBase {
+    /// The checked out commit, as in `git diff --staged`
+    #[default]
+    Head,
+    /// The first parent of the given commit, or the empty tree for a root commit.
+    /// When amending HEAD this is the diff of the amended commit as a whole.
+    ParentOf(String),
+    /// The first commit, diffed against the second commit instead of the index,
+    /// as in `git diff base tip`
+    Range(String, String),
+    /// The first parent of the given commit, or the empty tree for a root commit,
+    /// diffed against the commit instead of the index, as in `git show`
+    Commit(String),
+}
+
+/// A commit read from the history
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CommitInfo {
+    pub id: String,
+    pub message: String,
+}
+
+impl CommitInfo {
+    /// Returns the first line of the commit message
+    pub(crate) fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// Read access to the repository gptcommit runs in
+pub(crate) trait GitClient: Debug + Send + Sync {
+    /// Returns the root of the work tree
+    fn get_repo_root(&self) -> Result<PathBuf>;
+
+    /// Returns the directory git runs hooks from, which may not exist yet
+    fn get_hooks_path(&self) -> Result<PathBuf>;
+
+    /// Returns the git directory of the worktree, where git keeps files like
+    /// `COMMIT_EDITMSG`
+    fn get_git_dir(&self) -> Result<PathBuf>;
+
+    /// Returns the full id of the commit `rev` points to
+    fn resolve_commit(&self, rev: &str) -> Result<String>;
+
+    /// Returns the commits being merged into HEAD, as recorded in `MERGE_HEAD`
+    fn get_merge_heads(&self) -> Result<Vec<String>>;
+
+    /// Returns the commits reachable from `tip` but not from `base`, newest first,
+    /// as in `git log base..tip`
+    fn get_commits(&self, base: &str, tip: &str) -> Result<Vec<CommitInfo>>;
+
+    /// Returns the commit `rev` points to
+    fn get_commit(&self, rev: &str) -> Result<CommitInfo>;
+
+    /// Returns the best common ancestor of `one` and `two`, as in `git merge-base`
+    fn get_merge_base(&self, one: &str, two: &str) -> Result<String>;
+
+    /// Returns the full ids of the parents of the commit `rev`
+    fn get_parents(&self, rev: &str) -> Result<Vec<String>>;
+
+    /// Writes a copy of the commit `rev` with `message` on top of `parent`,
+    /// keeping its tree and author, and returns the id of the copy. No ref is
+    /// updated.
+    fn reword_commit(&self, rev: &str, parent: &str, message: &str) -> Result<String>;
+
+    /// Moves the branch HEAD points to, or HEAD itself when detached, from `old`
+    /// to `new`, recording `reason` in the reflog
+    fn update_head(&self, new: &str, old: &str, reason: &str) -> Result<()>;
+
+    /// Returns the most recent tag reachable from `rev`, as in
+    /// `git describe --tags --abbrev=0`, or `None` when there is none
+    fn get_latest_tag(&self, rev: &str) -> Result<Option<String>>;
+
+    /// Returns the branch `refs/remotes/<remote>/HEAD` points to, such as
+    /// `origin/main`, or `None` when the remote or its HEAD is unknown
+    fn get_remote_head(&self, remote: &str) -> Result<Option<String>>;
+
+    /// Returns the diff of the index, or of the commit range, against `base`,
+    /// limited to `pathspec`
+    fn get_staged_diff(
+        &self,
+        base: &DiffBase,
+        options: &DiffOptions,
+        pathspec: &[String],
+    ) -> Result<String>;
+
+    /// Returns the paths changed in the diff of the index, or of the commit range,
+    /// against `base`, limited to `pathspec`, in the order of the diff. Renames
+    /// are listed as the deleted and the added path.
+    fn get_changed_paths(&self, base: &DiffBase, pathspec: &[String]) -> Result<Vec<String>>;
+
+    /// Looks up `attributes` for each of `paths` in the staged `.gitattributes`
+    /// files.
+    ///
+    /// Returns the values of the specified attributes keyed by path, then by
+    /// attribute name. Attributes that are set without a value map to `set`.
+    fn get_attributes(
+        &self,
+        paths: &[&str],
+        attributes: &[&str],
+    ) -> Result<HashMap<String, HashMap<String, String>>>;
+
+    /// Returns the diff of the index against `base`, produced with the options of
+    /// the `diff` settings.
+    ///
+    /// Paths matching a `diff.overrides` entry are diffed again with the options
+    /// of the first matching override, and their file diffs take the place of the
+    /// original ones, keeping the order of the diff.
+    fn get_diffs(&self, base: &DiffBase, settings: &DiffSettings) -> Result<String> {
+        let options = DiffOptions::new(settings);
+        let pathspec = settings.pathspec.clone().unwrap_or_default();
+        let overrides = settings.overrides.clone().unwrap_or_default();
+        if overrides.is_empty() {
+            return self.get_staged_diff(base, &options, &pathspec);
+        }
+
+        let matchers = overrides
+            .iter()
+            .map(|diff_override| {
+                let mut builder = GitignoreBuilder::new("");
+                builder.add_line(None, &diff_override.path)?;
+                Ok(builder.build()?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let override_for_path = |path: &str| {
+            matchers.iter().position(|matcher| {
+                matcher
+                    .matched_path_or_any_parents(path.trim_start_matches('/'), false)
+                    .is_ignore()
+            })
+        };
+
+        // the base options may hide files, e.g. whitespace-only changes, so the
+        // files of each override are taken from the list of changed paths
+        let changed_paths = self.get_changed_paths(base, &pathspec)?;
+        let position = |path: &str| {
+            changed_paths
+                .iter()
+                .position(|changed_path| changed_path == path)
+                .unwrap_or(usize::MAX)
+        };
+        let mut file_diffs = diff::parse(&self.get_staged_diff(base, &options, &pathspec)?);
+        for (idx, diff_override) in overrides.iter().enumerate() {
+            // only the files of this override are diffed again, by their paths
+            // from the repository root, including where renamed files came from
+            let mut paths = changed_paths
+                .iter()
+                .filter(|path| override_for_path(path) == Some(idx))
+                .chain(
+                    file_diffs
+                        .iter()
+                        .filter(|file_diff| override_for_path(file_diff.path()) == Some(idx))
+                        .flat_map(|file_diff| [&file_diff.old_path, &file_diff.new_path])
+                        .flatten(),
+                )
+                .map(|path| format!(":(top,literal){path}"))
+                .collect::<Vec<String>>();
+            paths.sort();
+            paths.dedup();
+            if paths.is_empty() {
+                continue;
+            }
+            debug!(
+                "Diffing {} paths matching {} with overridden options",
+                paths.len(),
+                diff_override.path
+            );
+            let output =
+                self.get_staged_diff(base, &options.with_override(diff_override), &paths)?;
+            // keep the order of the diff, which `file_order = diff` relies on
+            for overridden in diff::parse(&output)
+                .into_iter()
+                .filter(|file_diff| override_for_path(file_diff.path()) == Some(idx))
+            {
+                match file_diffs
+                    .iter()
+                    .position(|file_diff| file_diff.path() == overridden.path())
+                {
+                    Some(idx) => file_diffs[idx] = overridden,
+                    None => {
+                        let idx = file_diffs
+                            .iter()
+                            .position(|file_diff| {
+                                position(file_diff.path()) > position(overridden.path())
+                            })
+                            .unwrap_or(file_diffs.len());
+                        file_diffs.insert(idx, overridden);
+                    }
+                }
+            }
+        }
+
+        Ok(file_diffs
+            .iter()
+            .map(|file_diff| file_diff.raw.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n"))
+    }
+}
+
+/// Returns the git client for the `git.backend` setting.
+pub(crate) fn get_git_client(settings: &Settings) -> Arc<dyn GitClient> {
+    let backend = settings
+        .git
+        .as_ref()
+        .and_then(|git| git.backend.as_deref())
+        .and_then(|backend| GitBackend::from_str(backend).ok())
+        .unwrap_or_default();
+    debug!("Using the {backend} git backend");
+    match backend {
+        GitBackend::Gix => Arc::new(GitoxideClient::default()),
+        GitBackend::Subprocess => Arc::new(SubprocessClient::default()),
+    }
+}
+
+/// Resolves `rev` into what its diff compares, as `git diff` and `git show`
+/// would: `a..b` compares both ends, `a...b` compares b with where it forked
+/// off a, and a single commit is compared with its first parent, or with the
+/// empty tree for a root commit.
+pub(crate) fn get_diff_range(git_client: &dyn GitClient, rev: &str) -> Result<DiffBase> {
+    if let Some((base, tip)) = rev.split_once("...") {
+        let tip = if tip.is_empty() { "HEAD" } else { tip };
+        let base = if base.is_empty() { "HEAD" } else { base };
+        return Ok(DiffBase::Range(
+            git_client.get_merge_base(base, tip)?,
+            tip.to_string(),
+        ));
+    }
+    if let Some((base, tip)) = rev.split_once("..") {
+        let tip = if tip.is_empty() { "HEAD" } else { tip };
+        let base = if base.is_empty() { "HEAD" } else { base };
+        return Ok(DiffBase::Range(base.to_string(), tip.to_string()));
+    }
+    Ok(DiffBase::Commit(git_client.resolve_commit(rev)?))
+}
+
+/// Returns the default branches of the upstream and origin remotes, such as
+/// `origin/main`: the branches their HEAD points to, or else their `main` or
+/// `master` branch.
+pub(crate) fn get_remote_default_branches(git_client: &dyn GitClient) -> Result<Vec<String>> {
+    let mut branches = Vec::new();
+    for remote in REMOTES {
+        match git_client.get_remote_head(remote)? {
+            Some(branch) => branches.push(branch),
+            None => branches.extend(
+                ["main", "master"]
+                    .iter()
+                    .map(|branch| format!("{remote}/{branch}"))
+                    .find(|branch| git_client.resolve_commit(branch).is_ok()),
+            ),
+        }
+    }
+    Ok(branches)
+}
+
+/// Returns the branch pull requests target by default: the default branch of the
+/// upstream or origin remote, falling back to a local `main` or `master` branch.
+pub(crate) fn get_default_branch(git_client: &dyn GitClient) -> Result<String> {
+    if let Some(branch) = get_remote_default_branches(git_client)?.into_iter().next() {
+        return Ok(branch);
+    }
+    for branch in ["main", "master"] {
+        if git_client.resolve_commit(branch).is_ok() {
+            return Ok(branch.to_string());
+        }
+    }
+    bail!("Could not find the default branch. Pass the branch to compare against with --base.")
+}
+
+/// Given current working directory, return path to .git/hooks
+///
+/// Settings are not loaded yet when the local config is looked up, so the
+/// repository is read in-process, falling back to the `git` executable.
+pub(crate) fn get_hooks_path() -> Result<PathBuf> {
+    let rel_hooks_path = GitoxideClient::default().get_hooks_path().or_else(|err| {
+        debug!("Could not find hooks path in-process, falling back to git: {err}");
+        SubprocessClient::default().get_hooks_path()
+    })?;
+    info!("Creating dir at {}", rel_hooks_path.display());
+    // create dirs first otherwise canonicalize will fail
+    fs::create_dir_all(&rel_hooks_path)?;
+    #[cfg(unix)]
+    fs::set_permissions(&rel_hooks_path, Permissions::from_mode(0o700))?;
+    // turn relative path into absolute path
+    let hooks_path = std::fs::canonicalize(rel_hooks_path)?;
+    Ok(hooks_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_diff_args() {
+        let settings = crate::settings::Settings::new().unwrap();
+        let options = DiffOptions::new(&settings.diff.unwrap());
+        assert_eq!(
+            options.to_args(),
+            [
+                "--diff-algorithm=minimal",
+                "--ignore-all-space",
+                "--function-context",
+                "--find-renames"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_override_diff_args() {
+        let options = DiffOptions::new(&DiffSettings {
+            algorithm: Some("histogram".to_string()),
+            context_lines: Some(5),
+            rename_detection: Some(false),
+            ..Default::default()
+        });
+        let overridden = options.with_override(&DiffOverride {
+            path: "*.py".to_string(),
+            whitespace: Some("keep".to_string()),
+            function_context: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(
+            overridden.to_args(),
+            ["--diff-algorithm=histogram", "--unified=5", "--no-renames"]
+        );
+
+        let invalid = options.with_override(&DiffOverride {
+            path: "*.py".to_string(),
+            algorithm: Some("fastest".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(invalid.algorithm, DiffAlgorithm::Histogram);
+    }
+}
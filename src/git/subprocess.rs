@@ -0,0 +1,227 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+
+use super::{CommitInfo, DiffBase, DiffOptions, GitClient};
+use crate::{cmd, encoding};
+
+/// Reads the repository by running the `git` executable.
+#[derive(Debug, Default)]
+pub(crate) struct SubprocessClient {}
+
+impl GitClient for SubprocessClient {
+    fn get_repo_root(&self) -> Result<PathBuf> {
+        let output = cmd::run_command("git", &["rev-parse", "--show-toplevel"])?;
+        Ok(PathBuf::from(output.trim_end()))
+    }
+
+    fn get_hooks_path(&self) -> Result<PathBuf> {
+        let output = cmd::run_command("git", &["rev-parse", "--git-path", "hooks"])?;
+        Ok(PathBuf::from(output.trim_end()))
+    }
+
+    fn get_git_dir(&self) -> Result<PathBuf> {
+        let output = cmd::run_command("git", &["rev-parse", "--absolute-git-dir"])?;
+        Ok(PathBuf::from(output.trim_end()))
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let output = cmd::run_command(
+            "git",
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{rev}^{{commit}}"),
+            ],
+        )?;
+        Ok(output.trim_end().to_string())
+    }
+
+    fn get_merge_heads(&self) -> Result<Vec<String>> {
+        let output = cmd::run_command("git", &["rev-parse", "--git-path", "MERGE_HEAD"])?;
+        let merge_head_path = PathBuf::from(output.trim_end());
+        if !merge_head_path.is_file() {
+            return Ok(vec![]);
+        }
+        Ok(fs::read_to_string(merge_head_path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn get_commits(&self, base: &str, tip: &str) -> Result<Vec<CommitInfo>> {
+        let range = format!("{base}..{tip}");
+        let output = cmd::run_command_raw("git", &["log", "-z", "--format=%H%n%B", &range, "--"])?;
+        Ok(String::from_utf8_lossy(&output)
+            .split('\0')
+            .filter_map(|entry| entry.split_once('\n'))
+            .map(|(id, message)| CommitInfo {
+                id: id.to_string(),
+                message: message.trim_end().to_string(),
+            })
+            .collect())
+    }
+
+    fn get_commit(&self, rev: &str) -> Result<CommitInfo> {
+        let output = cmd::run_command_raw("git", &["log", "-1", "--format=%H%n%B", rev, "--"])?;
+        let output = String::from_utf8_lossy(&output);
+        let (id, message) = output
+            .split_once('\n')
+            .ok_or(anyhow!("Could not read the commit {rev}"))?;
+        Ok(CommitInfo {
+            id: id.to_string(),
+            message: message.trim_end().to_string(),
+        })
+    }
+
+    fn get_merge_base(&self, one: &str, two: &str) -> Result<String> {
+        let output = cmd::run_command("git", &["merge-base", one, two])?;
+        Ok(output.trim_end().to_string())
+    }
+
+    fn get_parents(&self, rev: &str) -> Result<Vec<String>> {
+        let output = cmd::run_command("git", &["rev-list", "--parents", "-n", "1", rev])?;
+        Ok(output
+            .split_whitespace()
+            .skip(1)
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn reword_commit(&self, rev: &str, parent: &str, message: &str) -> Result<String> {
+        let tree = cmd::run_command("git", &["rev-parse", &format!("{rev}^{{tree}}")])?;
+        let author = cmd::run_command(
+            "git",
+            &["log", "-1", "--date=raw", "--format=%an%x00%ae%x00%ad", rev],
+        )?;
+        let author = author.trim_end().split('\0').collect::<Vec<&str>>();
+        let [name, email, date] = author.as_slice() else {
+            bail!("Could not read the author of {rev}");
+        };
+        let output = cmd::run_command_with_env(
+            "git",
+            &["commit-tree", tree.trim_end(), "-p", parent, "-F", "-"],
+            &[
+                ("GIT_AUTHOR_NAME", name),
+                ("GIT_AUTHOR_EMAIL", email),
+                ("GIT_AUTHOR_DATE", date),
+            ],
+            message.as_bytes(),
+        )?;
+        Ok(output.trim_end().to_string())
+    }
+
+    fn update_head(&self, new: &str, old: &str, reason: &str) -> Result<()> {
+        cmd::run_command("git", &["update-ref", "-m", reason, "HEAD", new, old])?;
+        Ok(())
+    }
+
+    fn get_latest_tag(&self, rev: &str) -> Result<Option<String>> {
+        // fails when no tag can be reached
+        let output = cmd::run_command("git", &["describe", "--tags", "--abbrev=0", rev]);
+        Ok(output.ok().map(|tag| tag.trim_end().to_string()))
+    }
+
+    fn get_remote_head(&self, remote: &str) -> Result<Option<String>> {
+        let remote_head = format!("refs/remotes/{remote}/HEAD");
+        // fails when the symbolic ref doesn't exist
+        let output = cmd::run_command("git", &["symbolic-ref", "--quiet", "--short", &remote_head]);
+        Ok(output.ok().map(|branch| branch.trim_end().to_string()))
+    }
+
+    fn get_staged_diff(
+        &self,
+        base: &DiffBase,
+        options: &DiffOptions,
+        pathspec: &[String],
+    ) -> Result<String> {
+        let revs = diff_revs(base)?;
+        let options_args = options.to_args();
+        let mut args = vec!["diff"];
+        args.extend(options_args.iter().map(String::as_str));
+        args.extend([
+            "--no-ext-diff",
+            "--no-color",
+            // keep paths parseable regardless of diff.noprefix and diff.mnemonicPrefix
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ]);
+        args.extend(revs.iter().map(String::as_str));
+        args.push("--");
+        args.extend(pathspec.iter().map(String::as_str));
+
+        let output = cmd::run_command_raw("git", &args)?;
+        Ok(encoding::decode_diff(&output))
+    }
+
+    fn get_changed_paths(&self, base: &DiffBase, pathspec: &[String]) -> Result<Vec<String>> {
+        let revs = diff_revs(base)?;
+        let mut args = vec!["diff", "--name-only", "-z", "--no-renames"];
+        args.extend(revs.iter().map(String::as_str));
+        args.push("--");
+        args.extend(pathspec.iter().map(String::as_str));
+
+        let output = cmd::run_command_raw("git", &args)?;
+        Ok(String::from_utf8_lossy(&output)
+            .split_terminator('\0')
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn get_attributes(
+        &self,
+        paths: &[&str],
+        attributes: &[&str],
+    ) -> Result<HashMap<String, HashMap<String, String>>> {
+        // the paths are relative to the repository root, not to the current directory
+        let repo_root = self.get_repo_root()?;
+        let repo_root = repo_root.to_string_lossy();
+        let mut args = vec!["-C", &repo_root, "check-attr", "-z", "--cached", "--stdin"];
+        args.extend_from_slice(attributes);
+        let input = paths
+            .iter()
+            .map(|path| format!("{path}\0"))
+            .collect::<String>();
+        let output = cmd::run_command_with_input("git", &args, input.as_bytes())?;
+
+        let mut attributes_for_path: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let fields = output.split('\0').collect::<Vec<&str>>();
+        for record in fields.chunks_exact(3) {
+            if let [path, attribute, value] = record {
+                if *value != "unspecified" {
+                    attributes_for_path
+                        .entry(path.to_string())
+                        .or_default()
+                        .insert(attribute.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(attributes_for_path)
+    }
+}
+
+/// Returns the first parent of `rev`, or the empty tree for a root commit
+fn parent_tree(rev: &str) -> Result<String> {
+    let output = cmd::run_command("git", &["rev-list", "--parents", "-n", "1", rev])?;
+    match output.split_whitespace().nth(1) {
+        Some(parent) => Ok(parent.to_string()),
+        None => {
+            let output =
+                cmd::run_command_with_input("git", &["hash-object", "-t", "tree", "--stdin"], b"")?;
+            Ok(output.trim_end().to_string())
+        }
+    }
+}
+
+/// Returns the revisions `git diff` compares for `base`
+fn diff_revs(base: &DiffBase) -> Result<Vec<String>> {
+    Ok(match base {
+        DiffBase::Head => vec!["--staged".to_string()],
+        DiffBase::ParentOf(rev) => vec!["--staged".to_string(), parent_tree(rev)?],
+        DiffBase::Range(base, tip) => vec![base.to_string(), tip.to_string()],
+        DiffBase::Commit(rev) => vec![parent_tree(rev)?, rev.to_string()],
+    })
+}
@@ -0,0 +1,22 @@
+use anyhow::{bail, Result};
+
+use async_trait::async_trait;
+
+use super::llm_client::LlmClient;
+
+#[derive(Clone, Debug)]
+/// Client of dry runs, which never sends a prompt to the model
+pub(crate) struct DryRunClient {}
+
+impl DryRunClient {
+    pub(crate) fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+}
+
+#[async_trait]
+impl LlmClient for DryRunClient {
+    async fn completions(&self, _prompt: &str) -> Result<String> {
+        bail!("Prompts are not sent to the model in a dry run")
+    }
+}
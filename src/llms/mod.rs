@@ -1,3 +1,56 @@
+pub(crate) mod dry_run;
 pub(crate) mod llm_client;
 pub(crate) mod openai;
 pub(crate) mod tester_foobar;
+
+use crate::help::print_help_openai_api_key;
+use crate::settings::{ModelProvider, Settings};
+
+use dry_run::DryRunClient;
+use llm_client::LlmClient;
+use openai::OpenAIClient;
+use tester_foobar::FooBarClient;
+
+/// Returns the name of the model the prompts are sent to
+pub(crate) fn get_model_name(settings: &Settings) -> String {
+    match settings {
+        Settings {
+            model_provider: Some(ModelProvider::OpenAI),
+            openai: Some(openai),
+            ..
+        } => openai.model.clone().unwrap_or_default(),
+        _ => settings
+            .model_provider
+            .clone()
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// Returns the client of the model provider chosen in the settings
+pub(crate) fn get_llm_client(settings: &Settings) -> Box<dyn LlmClient> {
+    match settings {
+        // no API key is needed when nothing is sent
+        Settings {
+            dry_run: Some(true),
+            ..
+        } => Box::new(DryRunClient::new().unwrap()),
+        Settings {
+            model_provider: Some(ModelProvider::TesterFoobar),
+            ..
+        } => Box::new(FooBarClient::new().unwrap()),
+        Settings {
+            model_provider: Some(ModelProvider::OpenAI),
+            openai: Some(openai),
+            ..
+        } => {
+            let client = OpenAIClient::new(openai.to_owned());
+            if let Err(_e) = client {
+                print_help_openai_api_key();
+                panic!("OpenAI API key not found in config or environment");
+            }
+            Box::new(client.unwrap())
+        }
+        _ => panic!("Could not load LLM Client from config!"),
+    }
+}
@@ -2,12 +2,19 @@
 extern crate log;
 
 mod actions;
+mod changelog;
 pub mod cli;
 mod cmd;
+mod diff;
+mod encoding;
+mod file_ignore;
 mod git;
 mod help;
 mod llms;
+mod output;
 mod prompt;
+mod redact;
+mod review;
 mod settings;
 mod summarize;
 mod toml;
@@ -40,8 +47,15 @@ async fn main() -> Result<()> {
     debug!("Settings: {:?}", settings);
 
     match cli_args.action {
+        Action::Commit(cli_args) => actions::commit::main(settings, cli_args).await,
+        Action::Summarize(cli_args) => actions::summarize::main(settings, cli_args).await,
+        Action::Review(cli_args) => actions::review::main(settings, cli_args).await,
+        Action::Pr(cli_args) => actions::pr::main(settings, cli_args).await,
+        Action::Reword(cli_args) => actions::reword::main(settings, cli_args).await,
+        Action::Explain(cli_args) => actions::explain::main(settings, cli_args).await,
+        Action::Changelog(cli_args) => actions::changelog::main(settings, cli_args).await,
         Action::Config(cli_args) => actions::config::main(settings, cli_args).await,
-        Action::Install => actions::install::main(settings).await,
+        Action::Install(cli_args) => actions::install::main(settings, cli_args).await,
         Action::Uninstall => actions::uninstall::main(settings).await,
         Action::PrepareCommitMsg(cli_args) => {
             actions::prepare_commit_msg::main(settings, cli_args).await
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::changelog::ChangelogEntry;
+use crate::git::CommitInfo;
+use crate::summarize::SummarizationReport;
+
+/// Version of the JSON output, only bumped when a field changes or goes away
+pub(crate) const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// How a generated message is printed
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// The message as is
+    #[default]
+    Text,
+    /// A JSON object with the message, its parts and how it was generated
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct FileSummaryOutput {
+    pub file_name: String,
+    pub summary: String,
+    pub lines_changed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SkippedFileOutput {
+    pub file_name: String,
+    pub reason: String,
+}
+
+/// Tokens are estimated with the cl100k tokenizer, as providers may count them
+/// differently
+#[derive(Debug, Serialize)]
+pub(crate) struct UsageOutput {
+    pub model: String,
+    pub requests: usize,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TimingOutput {
+    pub duration_ms: u128,
+}
+
+/// A generated message as printed with `--format json`
+#[derive(Debug, Serialize)]
+pub(crate) struct MessageOutput {
+    pub schema_version: u32,
+    /// First line of the message, with its conventional commit prefix
+    pub title: String,
+    pub body: String,
+    pub message: String,
+    /// Conventional commit type of the title, such as `feat`
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub file_summaries: Vec<FileSummaryOutput>,
+    pub skipped_files: Vec<SkippedFileOutput>,
+    pub warnings: Vec<String>,
+    pub usage: UsageOutput,
+    pub timing: TimingOutput,
+}
+
+impl MessageOutput {
+    pub(crate) fn new(message: &str, report: SummarizationReport, duration: Duration) -> Self {
+        let message = message.trim();
+        let (title, body) = message.split_once("\n\n").unwrap_or((message, ""));
+        let entry = ChangelogEntry::parse(&CommitInfo {
+            id: String::new(),
+            message: message.to_string(),
+        });
+        Self {
+            schema_version: JSON_SCHEMA_VERSION,
+            title: title.trim().to_string(),
+            body: body.trim().to_string(),
+            message: message.to_string(),
+            kind: entry.kind,
+            scope: entry.scope,
+            breaking: entry.breaking,
+            file_summaries: report
+                .file_summaries
+                .into_iter()
+                .map(|file| FileSummaryOutput {
+                    file_name: file.file_name,
+                    summary: file.summary,
+                    lines_changed: file.lines_changed,
+                })
+                .collect(),
+            skipped_files: report
+                .skipped_files
+                .into_iter()
+                .map(|file| SkippedFileOutput {
+                    file_name: file.file_name,
+                    reason: file.reason,
+                })
+                .collect(),
+            warnings: report.warnings,
+            usage: UsageOutput {
+                model: report.model,
+                requests: report.usage.requests,
+                prompt_tokens: report.usage.prompt_tokens,
+                completion_tokens: report.usage.completion_tokens,
+            },
+            timing: TimingOutput {
+                duration_ms: duration.as_millis(),
+            },
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        // only strings, numbers and booleans, which always serialize
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summarize::{FileSummary, SkippedFile, TokenUsage};
+
+    #[test]
+    fn test_message_output() {
+        let report = SummarizationReport {
+            file_summaries: vec![FileSummary {
+                file_name: "src/cli.rs".to_string(),
+                summary: "- Add a flag".to_string(),
+                lines_changed: 3,
+            }],
+            skipped_files: vec![SkippedFile {
+                file_name: "Cargo.lock".to_string(),
+                reason: "skipped by gitattributes".to_string(),
+            }],
+            warnings: vec![],
+            model: "gpt-4.1-nano".to_string(),
+            usage: TokenUsage {
+                requests: 4,
+                prompt_tokens: 400,
+                completion_tokens: 40,
+            },
+        };
+        let output = MessageOutput::new(
+            "feat(cli): Add a flag\n\n- Add a flag\n",
+            report,
+            Duration::from_millis(1500),
+        );
+        assert_eq!(
+            output.to_json(),
+            r#"{
+  "schema_version": 1,
+  "title": "feat(cli): Add a flag",
+  "body": "- Add a flag",
+  "message": "feat(cli): Add a flag\n\n- Add a flag",
+  "type": "feat",
+  "scope": "cli",
+  "breaking": false,
+  "file_summaries": [
+    {
+      "file_name": "src/cli.rs",
+      "summary": "- Add a flag",
+      "lines_changed": 3
+    }
+  ],
+  "skipped_files": [
+    {
+      "file_name": "Cargo.lock",
+      "reason": "skipped by gitattributes"
+    }
+  ],
+  "warnings": [],
+  "usage": {
+    "model": "gpt-4.1-nano",
+    "requests": 4,
+    "prompt_tokens": 400,
+    "completion_tokens": 40
+  },
+  "timing": {
+    "duration_ms": 1500
+  }
+}"#
+        );
+    }
+}
@@ -9,10 +9,23 @@ pub fn format_prompt(prompt: &str, map: HashMap<&str, &str>) -> Result<String, E
     Tera::one_off(prompt, &context, false)
 }
 
+pub static PROMPT_TO_CHECK_COMMIT_MESSAGE: &str =
+    include_str!("../prompts/check_commit_message.tera");
 pub static PROMPT_TO_CONVENTIONAL_COMMIT_PREFIX: &str =
     include_str!("../prompts/conventional_commit.tera");
+pub static PROMPT_TO_DESCRIBE_PR: &str = include_str!("../prompts/describe_pr.tera");
+pub static PROMPT_TO_FILL_COMMIT_TEMPLATE: &str =
+    include_str!("../prompts/fill_commit_template.tera");
+pub static PROMPT_TO_REVIEW_DIFF: &str = include_str!("../prompts/review_file_diff.tera");
+pub static PROMPT_TO_SUMMARIZE_CHANGELOG_SECTION: &str =
+    include_str!("../prompts/summarize_changelog_section.tera");
 pub static PROMPT_TO_SUMMARIZE_DIFF: &str = include_str!("../prompts/summarize_file_diff.tera");
 pub static PROMPT_TO_SUMMARIZE_DIFF_SUMMARIES: &str =
     include_str!("../prompts/summarize_commit.tera");
+pub static PROMPT_TO_SUMMARIZE_FILE_GROUP: &str =
+    include_str!("../prompts/summarize_file_group.tera");
+pub static PROMPT_TO_SUMMARIZE_MERGE: &str = include_str!("../prompts/summarize_merge.tera");
+pub static PROMPT_TO_SUMMARIZE_SQUASH: &str = include_str!("../prompts/summarize_squash.tera");
 pub static PROMPT_TO_SUMMARIZE_DIFF_TITLE: &str = include_str!("../prompts/title_commit.tera");
+pub static PROMPT_TO_TITLE_PR: &str = include_str!("../prompts/title_pr.tera");
 pub static PROMPT_TO_TRANSLATE: &str = include_str!("../prompts/translation.tera");
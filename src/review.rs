@@ -0,0 +1,165 @@
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::diff::FileDiff;
+use crate::settings::ReviewSeverity;
+
+lazy_static! {
+    static ref FINDING_LINE: Regex = Regex::new(
+        r"(?i)^\[?(?P<severity>low|medium|high)\]?(?:\s+\(?l(?:ine)?\s*(?P<line>\d+)\)?)?\s*:\s*(?P<message>\S.*)$"
+    )
+    .unwrap();
+}
+
+/// A problem the model found while reviewing a file diff
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReviewFinding {
+    pub file_name: String,
+    /// Line in the new file, if the finding is about a changed line
+    pub line: Option<usize>,
+    pub severity: ReviewSeverity,
+    pub message: String,
+}
+
+impl ReviewFinding {
+    /// The file and line, as `path:line` or just the path
+    pub(crate) fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{line}", self.file_name),
+            None => self.file_name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ReviewFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}",
+            self.location(),
+            self.severity,
+            self.message
+        )
+    }
+}
+
+/// Renders the hunks of the diff with the line number in the new file in front
+/// of every added and context line, so the model can point at lines.
+pub(crate) fn number_lines(file_diff: &FileDiff) -> String {
+    let mut numbered = String::with_capacity(file_diff.raw.len());
+    for hunk in &file_diff.hunks {
+        numbered.push_str(
+            format!(
+                "@@ -{},{} +{},{} @@ {}",
+                hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines, hunk.section
+            )
+            .trim_end(),
+        );
+        numbered.push('\n');
+        let mut new_line = hunk.new_start;
+        for line in &hunk.lines {
+            if line.starts_with('+') || line.starts_with(' ') {
+                numbered.push_str(&format!("{new_line:>5} {line}\n"));
+                new_line += 1;
+            } else {
+                numbered.push_str(&format!("{:>5} {line}\n", ""));
+            }
+        }
+    }
+    numbered
+}
+
+/// Reads the findings of a file review, one per line. Lines that don't follow
+/// the requested format are kept as low severity findings about the whole
+/// file, and findings pointing outside of the changed hunks are kept without
+/// their line number.
+pub(crate) fn parse_findings(file_diff: &FileDiff, completion: &str) -> Vec<ReviewFinding> {
+    let in_hunk = |line: usize| {
+        file_diff
+            .hunks
+            .iter()
+            .any(|hunk| (hunk.new_start..hunk.new_start + hunk.new_lines).contains(&line))
+    };
+    completion
+        .lines()
+        .map(|line| line.trim().trim_start_matches(['-', '*']).trim())
+        .filter(|line| !line.is_empty() && !line.eq_ignore_ascii_case("none"))
+        .map(|line| match FINDING_LINE.captures(line) {
+            Some(caps) => ReviewFinding {
+                file_name: file_diff.path().to_string(),
+                line: caps
+                    .name("line")
+                    .and_then(|line| line.as_str().parse().ok())
+                    .filter(|line| in_hunk(*line)),
+                severity: ReviewSeverity::from_str(&caps["severity"].to_lowercase())
+                    .unwrap_or_default(),
+                message: caps["message"].trim().to_string(),
+            },
+            None => ReviewFinding {
+                file_name: file_diff.path().to_string(),
+                line: None,
+                severity: ReviewSeverity::Low,
+                message: line.to_string(),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff;
+
+    static DIFF: &str = "diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -10,3 +10,4 @@ fn main() {
+     let x = 1;
+-    run(x);
++    dbg!(x);
++    run(x);
+ }
+";
+
+    #[test]
+    fn test_number_lines() {
+        let file_diff = diff::parse(DIFF).remove(0);
+        assert_eq!(
+            number_lines(&file_diff),
+            "@@ -10,3 +10,4 @@ fn main() {
+   10      let x = 1;
+      -    run(x);
+   11 +    dbg!(x);
+   12 +    run(x);
+   13  }
+"
+        );
+    }
+
+    #[test]
+    fn test_parse_findings() {
+        let file_diff = diff::parse(DIFF).remove(0);
+        assert!(parse_findings(&file_diff, "NONE\n").is_empty());
+
+        let findings = parse_findings(
+            &file_diff,
+            "- high L11: Leftover dbg! call\n- Medium: No test covers run\n- low L99: Typo\nLooks fine otherwise\n",
+        );
+        assert_eq!(
+            findings
+                .iter()
+                .map(ReviewFinding::to_string)
+                .collect::<Vec<String>>(),
+            [
+                "src/main.rs:11: high: Leftover dbg! call",
+                "src/main.rs: medium: No test covers run",
+                "src/main.rs: low: Typo",
+                "src/main.rs: low: Looks fine otherwise",
+            ]
+        );
+    }
+}
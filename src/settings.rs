@@ -15,16 +15,24 @@ use std::string::ToString;
 use strum_macros::{Display, IntoStaticStr};
 
 use crate::{
+    changelog::CHANGELOG_TEMPLATE,
     git::get_hooks_path,
     prompt::{
-        PROMPT_TO_CONVENTIONAL_COMMIT_PREFIX, PROMPT_TO_SUMMARIZE_DIFF,
-        PROMPT_TO_SUMMARIZE_DIFF_SUMMARIES, PROMPT_TO_SUMMARIZE_DIFF_TITLE, PROMPT_TO_TRANSLATE,
+        PROMPT_TO_CHECK_COMMIT_MESSAGE, PROMPT_TO_CONVENTIONAL_COMMIT_PREFIX,
+        PROMPT_TO_DESCRIBE_PR, PROMPT_TO_FILL_COMMIT_TEMPLATE, PROMPT_TO_REVIEW_DIFF,
+        PROMPT_TO_SUMMARIZE_CHANGELOG_SECTION, PROMPT_TO_SUMMARIZE_DIFF,
+        PROMPT_TO_SUMMARIZE_DIFF_SUMMARIES, PROMPT_TO_SUMMARIZE_DIFF_TITLE,
+        PROMPT_TO_SUMMARIZE_FILE_GROUP, PROMPT_TO_SUMMARIZE_MERGE, PROMPT_TO_SUMMARIZE_SQUASH,
+        PROMPT_TO_TITLE_PR, PROMPT_TO_TRANSLATE,
     },
 };
 
 // Use the fastest and cheapest model available by default
 static DEFAULT_OPENAI_MODEL: &str = "gpt-4.1-nano";
 
+// Keep the commit-level prompts well inside the smallest supported context window
+const DEFAULT_SUMMARY_POINTS_TOKEN_LIMIT: u32 = 4000;
+
 static DEFAULT_FILES_TO_IGNORE: &[&str; 8] = &[
     "bun.lockb",
     "Cargo.lock",
@@ -118,10 +126,19 @@ impl From<OpenAISettings> for config::ValueKind {
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub(crate) struct PromptSettings {
+    pub changelog_summary: Option<String>,
     pub conventional_commit_prefix: Option<String>,
     pub commit_summary: Option<String>,
+    pub commit_template: Option<String>,
     pub commit_title: Option<String>,
     pub file_diff: Option<String>,
+    pub file_group: Option<String>,
+    pub file_review: Option<String>,
+    pub merge_summary: Option<String>,
+    pub message_check: Option<String>,
+    pub pr_description: Option<String>,
+    pub pr_title: Option<String>,
+    pub squash_summary: Option<String>,
     pub translation: Option<String>,
 }
 
@@ -130,6 +147,10 @@ impl From<PromptSettings> for config::ValueKind {
     fn from(settings: PromptSettings) -> Self {
         let mut properties = HashMap::new();
 
+        properties.insert(
+            "changelog_summary".to_string(),
+            config::Value::from(settings.changelog_summary),
+        );
         properties.insert(
             "conventional_commit_prefix".to_string(),
             config::Value::from(settings.conventional_commit_prefix),
@@ -138,6 +159,10 @@ impl From<PromptSettings> for config::ValueKind {
             "commit_summary".to_string(),
             config::Value::from(settings.commit_summary),
         );
+        properties.insert(
+            "commit_template".to_string(),
+            config::Value::from(settings.commit_template),
+        );
         properties.insert(
             "commit_title".to_string(),
             config::Value::from(settings.commit_title),
@@ -146,6 +171,34 @@ impl From<PromptSettings> for config::ValueKind {
             "file_diff".to_string(),
             config::Value::from(settings.file_diff),
         );
+        properties.insert(
+            "file_group".to_string(),
+            config::Value::from(settings.file_group),
+        );
+        properties.insert(
+            "file_review".to_string(),
+            config::Value::from(settings.file_review),
+        );
+        properties.insert(
+            "merge_summary".to_string(),
+            config::Value::from(settings.merge_summary),
+        );
+        properties.insert(
+            "message_check".to_string(),
+            config::Value::from(settings.message_check),
+        );
+        properties.insert(
+            "pr_description".to_string(),
+            config::Value::from(settings.pr_description),
+        );
+        properties.insert(
+            "pr_title".to_string(),
+            config::Value::from(settings.pr_title),
+        );
+        properties.insert(
+            "squash_summary".to_string(),
+            config::Value::from(settings.squash_summary),
+        );
         properties.insert(
             "translation".to_string(),
             config::Value::from(settings.translation),
@@ -182,6 +235,10 @@ pub struct OutputSettings {
     pub lang: Option<String>,
     /// Whether to show the summary of each file in the commit
     pub show_per_file_summary: Option<bool>,
+    /// Number of candidate commit messages to generate
+    pub candidates: Option<u32>,
+    /// Whether each candidate gets its own body instead of sharing the first one
+    pub candidate_bodies: Option<bool>,
 }
 
 // implement the trait `From<OutputSettings>` for `ValueKind`
@@ -201,6 +258,349 @@ impl From<OutputSettings> for config::ValueKind {
             "show_per_file_summary".to_string(),
             config::Value::from(settings.show_per_file_summary),
         );
+        properties.insert(
+            "candidates".to_string(),
+            config::Value::from(settings.candidates),
+        );
+        properties.insert(
+            "candidate_bodies".to_string(),
+            config::Value::from(settings.candidate_bodies),
+        );
+        Self::Table(properties)
+    }
+}
+
+/// Order in which file summaries are passed to the model and shown
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum FileOrder {
+    /// Keep the order of the files in the diff
+    #[default]
+    Diff,
+    /// Sort by file path
+    Path,
+    /// Largest change first
+    Size,
+    /// Source code before tests, docs and config, then largest change first
+    Importance,
+}
+
+/// What to do when a file diff cannot be summarized
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum FileErrorPolicy {
+    /// Abort the run
+    Fail,
+    /// Leave the file out of the commit message
+    #[default]
+    Warn,
+    /// Describe the file from its diff stats instead
+    Heuristic,
+}
+
+/// How a file is summarized, as set with the `gptcommit` git attribute
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum FileHandling {
+    /// Leave the file out of the commit message
+    Skip,
+    /// Describe the file from its diff stats without sending the diff
+    #[default]
+    SummaryOnly,
+    /// Send the diff for summarization
+    Full,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SummarizationSettings {
+    /// Token budget for the combined file summaries passed to the commit prompts.
+    /// Above it, summaries are grouped by directory and summarized again.
+    pub summary_points_token_limit: Option<u32>,
+    /// Order of the file summaries, one of diff, path, size or importance
+    pub file_order: Option<String>,
+    /// Handling of files that fail to summarize, one of fail, warn or heuristic
+    pub file_error_policy: Option<String>,
+    /// Handling of files marked `linguist-generated` or `linguist-vendored`
+    /// in .gitattributes, one of skip, summary-only or full
+    pub generated_files: Option<String>,
+}
+
+// implement the trait `From<SummarizationSettings>` for `ValueKind`
+impl From<SummarizationSettings> for config::ValueKind {
+    fn from(settings: SummarizationSettings) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert(
+            "summary_points_token_limit".to_string(),
+            config::Value::from(settings.summary_points_token_limit),
+        );
+        properties.insert(
+            "file_order".to_string(),
+            config::Value::from(settings.file_order),
+        );
+        properties.insert(
+            "file_error_policy".to_string(),
+            config::Value::from(settings.file_error_policy),
+        );
+        properties.insert(
+            "generated_files".to_string(),
+            config::Value::from(settings.generated_files),
+        );
+        Self::Table(properties)
+    }
+}
+
+/// Diff algorithm passed to `git diff --diff-algorithm`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum DiffAlgorithm {
+    Myers,
+    #[default]
+    Minimal,
+    Patience,
+    Histogram,
+}
+
+/// Whitespace handling of the staged diff
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum DiffWhitespace {
+    /// `--ignore-all-space`
+    #[default]
+    IgnoreAll,
+    /// `--ignore-space-change`
+    IgnoreChange,
+    /// `--ignore-space-at-eol`
+    IgnoreAtEol,
+    /// Keep whitespace changes, for whitespace-sensitive languages
+    Keep,
+}
+
+/// Diff options for the paths matching a pattern
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiffOverride {
+    /// Pattern matched against staged paths, following gitignore rules
+    pub path: String,
+    pub algorithm: Option<String>,
+    pub whitespace: Option<String>,
+    pub context_lines: Option<u32>,
+    pub function_context: Option<bool>,
+}
+
+// implement the trait `From<DiffOverride>` for `ValueKind`
+impl From<DiffOverride> for config::ValueKind {
+    fn from(settings: DiffOverride) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("path".to_string(), config::Value::from(settings.path));
+        properties.insert(
+            "algorithm".to_string(),
+            config::Value::from(settings.algorithm),
+        );
+        properties.insert(
+            "whitespace".to_string(),
+            config::Value::from(settings.whitespace),
+        );
+        properties.insert(
+            "context_lines".to_string(),
+            config::Value::from(settings.context_lines),
+        );
+        properties.insert(
+            "function_context".to_string(),
+            config::Value::from(settings.function_context),
+        );
+        Self::Table(properties)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct DiffSettings {
+    /// Diff algorithm, one of myers, minimal, patience or histogram
+    pub algorithm: Option<String>,
+    /// Whitespace handling, one of ignore-all, ignore-change, ignore-at-eol or keep
+    pub whitespace: Option<String>,
+    /// Lines of context around each change, git's default when unset
+    pub context_lines: Option<u32>,
+    /// Whether to show the whole function around each change
+    pub function_context: Option<bool>,
+    /// Whether to detect renamed files
+    pub rename_detection: Option<bool>,
+    /// Pathspecs limiting which staged files are summarized
+    pub pathspec: Option<Vec<String>>,
+    /// Options for specific paths, the first matching override wins
+    pub overrides: Option<Vec<DiffOverride>>,
+}
+
+// implement the trait `From<DiffSettings>` for `ValueKind`
+impl From<DiffSettings> for config::ValueKind {
+    fn from(settings: DiffSettings) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert(
+            "algorithm".to_string(),
+            config::Value::from(settings.algorithm),
+        );
+        properties.insert(
+            "whitespace".to_string(),
+            config::Value::from(settings.whitespace),
+        );
+        properties.insert(
+            "context_lines".to_string(),
+            config::Value::from(settings.context_lines),
+        );
+        properties.insert(
+            "function_context".to_string(),
+            config::Value::from(settings.function_context),
+        );
+        properties.insert(
+            "rename_detection".to_string(),
+            config::Value::from(settings.rename_detection),
+        );
+        properties.insert(
+            "pathspec".to_string(),
+            config::Value::from(settings.pathspec),
+        );
+        properties.insert(
+            "overrides".to_string(),
+            config::Value::from(settings.overrides),
+        );
+        Self::Table(properties)
+    }
+}
+
+/// What to do when secrets are found in the text sent to the model
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RedactionAction {
+    /// Replace secrets with placeholders
+    #[default]
+    Redact,
+    /// Abort without sending anything
+    Abort,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct RedactionSettings {
+    /// Whether to scan prompts for secrets before they are sent
+    pub enabled: Option<bool>,
+    /// What to do with found secrets, one of redact or abort
+    pub action: Option<String>,
+    /// Extra regular expressions matching secrets
+    pub patterns: Option<Vec<String>>,
+}
+
+// implement the trait `From<RedactionSettings>` for `ValueKind`
+impl From<RedactionSettings> for config::ValueKind {
+    fn from(settings: RedactionSettings) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("enabled".to_string(), config::Value::from(settings.enabled));
+        properties.insert("action".to_string(), config::Value::from(settings.action));
+        properties.insert(
+            "patterns".to_string(),
+            config::Value::from(settings.patterns),
+        );
+        Self::Table(properties)
+    }
+}
+
+/// How the githook handles a commit started from `commit.template`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum TemplateMode {
+    /// Leave the template untouched
+    #[default]
+    Skip,
+    /// Replace the `{{ title }}` and `{{ body }}` placeholders of the template
+    Placeholders,
+    /// Ask the model to fill in the sections of the template
+    Prompt,
+}
+
+/// How the githook handles a commit message given with `-m` or `-F`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum MessageMode {
+    /// Leave the message untouched
+    #[default]
+    Skip,
+    /// Keep the subject line and replace the rest with a body written around it
+    Expand,
+    /// Keep the whole message and add a generated body below it
+    Append,
+}
+
+/// How gptcommit reads the repository
+#[derive(Debug, Default, Clone, Copy, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum GitBackend {
+    /// Read the repository in-process with gitoxide
+    #[default]
+    Gix,
+    /// Shell out to the `git` executable
+    Subprocess,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct GitSettings {
+    /// How to read the repository, one of gix or subprocess
+    pub backend: Option<String>,
+}
+
+// implement the trait `From<GitSettings>` for `ValueKind`
+impl From<GitSettings> for config::ValueKind {
+    fn from(settings: GitSettings) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("backend".to_string(), config::Value::from(settings.backend));
+        Self::Table(properties)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ChangelogSettings {
+    /// Tera template the release notes are rendered with
+    pub template: Option<String>,
+    /// Whether to add a summary written by the model to each section
+    pub summarize: Option<bool>,
+}
+
+// implement the trait `From<ChangelogSettings>` for `ValueKind`
+impl From<ChangelogSettings> for config::ValueKind {
+    fn from(settings: ChangelogSettings) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert(
+            "template".to_string(),
+            config::Value::from(settings.template),
+        );
+        properties.insert(
+            "summarize".to_string(),
+            config::Value::from(settings.summarize),
+        );
+        Self::Table(properties)
+    }
+}
+
+/// Severity of a review finding, from least to most severe
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ReviewSeverity {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+/// Value of `review.fail_on` that never fails a review
+pub(crate) static REVIEW_NEVER_FAIL: &str = "never";
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ReviewSettings {
+    /// Lowest severity of the findings that fail the review, one of low,
+    /// medium, high or never
+    pub fail_on: Option<String>,
+}
+
+// implement the trait `From<ReviewSettings>` for `ValueKind`
+impl From<ReviewSettings> for config::ValueKind {
+    fn from(settings: ReviewSettings) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("fail_on".to_string(), config::Value::from(settings.fail_on));
         Self::Table(properties)
     }
 }
@@ -211,9 +611,26 @@ pub(crate) struct Settings {
     pub openai: Option<OpenAISettings>,
     pub prompt: Option<PromptSettings>,
     pub output: Option<OutputSettings>,
+    pub summarization: Option<SummarizationSettings>,
+    pub redaction: Option<RedactionSettings>,
+    pub diff: Option<DiffSettings>,
+    pub git: Option<GitSettings>,
+    pub changelog: Option<ChangelogSettings>,
+    pub review: Option<ReviewSettings>,
+    /// Whether to print the prompts instead of sending them to the model
+    pub dry_run: Option<bool>,
     /// Whether to run githook when amending the commit
     pub allow_amend: Option<bool>,
-    /// Files to ignore, format similar to gitignore
+    /// Whether to run githook for merge commits
+    pub allow_merge: Option<bool>,
+    /// Whether to run githook for squashed commits
+    pub allow_squash: Option<bool>,
+    /// Handling of commits started from `commit.template`, one of skip,
+    /// placeholders or prompt
+    pub template_mode: Option<String>,
+    /// Handling of messages given with `-m` or `-F`, one of skip, expand or append
+    pub message_mode: Option<String>,
+    /// Files to ignore, in gitignore format
     pub file_ignore: Option<Vec<String>>,
 }
 
@@ -228,6 +645,59 @@ impl Settings {
         if key == "output.lang" && Language::from_str(value).is_err() {
             return Err(ConfigError::Message(format!("Invalid language: {value}.",)));
         }
+        if key == "summarization.file_order" && FileOrder::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid file order: {value}.",
+            )));
+        }
+        if key == "summarization.file_error_policy" && FileErrorPolicy::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid file error policy: {value}.",
+            )));
+        }
+        if key == "summarization.generated_files" && FileHandling::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid generated file handling: {value}.",
+            )));
+        }
+        if key == "redaction.action" && RedactionAction::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid redaction action: {value}.",
+            )));
+        }
+        if key == "diff.algorithm" && DiffAlgorithm::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid diff algorithm: {value}.",
+            )));
+        }
+        if key == "diff.whitespace" && DiffWhitespace::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid diff whitespace mode: {value}.",
+            )));
+        }
+        if key == "template_mode" && TemplateMode::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid template mode: {value}.",
+            )));
+        }
+        if key == "message_mode" && MessageMode::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid message mode: {value}.",
+            )));
+        }
+        if key == "git.backend" && GitBackend::from_str(value).is_err() {
+            return Err(ConfigError::Message(format!(
+                "Invalid git backend: {value}.",
+            )));
+        }
+        if key == "review.fail_on"
+            && value != REVIEW_NEVER_FAIL
+            && ReviewSeverity::from_str(value).is_err()
+        {
+            return Err(ConfigError::Message(format!(
+                "Invalid review severity: {value}.",
+            )));
+        }
         let mut settings = Self::get_config_builder()?;
         settings = settings.set_override(key, value)?;
         settings.build()?.try_deserialize()
@@ -241,6 +711,11 @@ impl Settings {
     fn get_config_builder() -> Result<ConfigBuilder<DefaultState>, ConfigError> {
         let mut settings = Config::builder()
             .set_default("allow_amend", false)?
+            .set_default("allow_merge", false)?
+            .set_default("allow_squash", false)?
+            .set_default("dry_run", false)?
+            .set_default("template_mode", TemplateMode::Skip.to_string())?
+            .set_default("message_mode", MessageMode::Skip.to_string())?
             .set_default(
                 "file_ignore",
                 DEFAULT_FILES_TO_IGNORE
@@ -263,12 +738,21 @@ impl Settings {
             .set_default(
                 "prompt",
                 Some(PromptSettings {
+                    changelog_summary: Some(PROMPT_TO_SUMMARIZE_CHANGELOG_SECTION.to_string()),
                     conventional_commit_prefix: Some(
                         PROMPT_TO_CONVENTIONAL_COMMIT_PREFIX.to_string(),
                     ),
                     file_diff: Some(PROMPT_TO_SUMMARIZE_DIFF.to_string()),
+                    file_group: Some(PROMPT_TO_SUMMARIZE_FILE_GROUP.to_string()),
+                    file_review: Some(PROMPT_TO_REVIEW_DIFF.to_string()),
                     commit_summary: Some(PROMPT_TO_SUMMARIZE_DIFF_SUMMARIES.to_string()),
+                    commit_template: Some(PROMPT_TO_FILL_COMMIT_TEMPLATE.to_string()),
                     commit_title: Some(PROMPT_TO_SUMMARIZE_DIFF_TITLE.to_string()),
+                    merge_summary: Some(PROMPT_TO_SUMMARIZE_MERGE.to_string()),
+                    message_check: Some(PROMPT_TO_CHECK_COMMIT_MESSAGE.to_string()),
+                    pr_description: Some(PROMPT_TO_DESCRIBE_PR.to_string()),
+                    pr_title: Some(PROMPT_TO_TITLE_PR.to_string()),
+                    squash_summary: Some(PROMPT_TO_SUMMARIZE_SQUASH.to_string()),
                     translation: Some(PROMPT_TO_TRANSLATE.to_string()),
                 }),
             )?
@@ -279,6 +763,56 @@ impl Settings {
                     conventional_commit_prefix_format: Some("{{ prefix }}: ".to_string()),
                     lang: Some("en".to_string()),
                     show_per_file_summary: Some(false),
+                    candidates: Some(1),
+                    candidate_bodies: Some(false),
+                }),
+            )?
+            .set_default(
+                "summarization",
+                Some(SummarizationSettings {
+                    summary_points_token_limit: Some(DEFAULT_SUMMARY_POINTS_TOKEN_LIMIT),
+                    file_order: Some(FileOrder::Diff.to_string()),
+                    file_error_policy: Some(FileErrorPolicy::Warn.to_string()),
+                    generated_files: Some(FileHandling::SummaryOnly.to_string()),
+                }),
+            )?
+            .set_default(
+                "redaction",
+                Some(RedactionSettings {
+                    enabled: Some(true),
+                    action: Some(RedactionAction::Redact.to_string()),
+                    patterns: Some(vec![]),
+                }),
+            )?
+            .set_default(
+                "diff",
+                Some(DiffSettings {
+                    algorithm: Some(DiffAlgorithm::Minimal.to_string()),
+                    whitespace: Some(DiffWhitespace::IgnoreAll.to_string()),
+                    context_lines: None,
+                    function_context: Some(true),
+                    rename_detection: Some(true),
+                    pathspec: Some(vec![]),
+                    overrides: Some(vec![]),
+                }),
+            )?
+            .set_default(
+                "git",
+                Some(GitSettings {
+                    backend: Some(GitBackend::Gix.to_string()),
+                }),
+            )?
+            .set_default(
+                "changelog",
+                Some(ChangelogSettings {
+                    template: Some(CHANGELOG_TEMPLATE.to_string()),
+                    summarize: Some(false),
+                }),
+            )?
+            .set_default(
+                "review",
+                Some(ReviewSettings {
+                    fail_on: Some(ReviewSeverity::High.to_string()),
                 }),
             )?;
 
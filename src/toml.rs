@@ -71,22 +71,55 @@ the-force = { value = "surrounds-you" }
     fn get_config_keys() -> Vec<&'static str> {
         vec![
             "allow_amend",
+            "allow_merge",
+            "allow_squash",
+            "changelog.summarize",
+            "changelog.template",
+            "diff.algorithm",
+            "diff.function_context",
+            "diff.overrides",
+            "diff.pathspec",
+            "diff.rename_detection",
+            "diff.whitespace",
+            "dry_run",
             "file_ignore",
+            "git.backend",
+            "message_mode",
             "model_provider",
             "openai.api_base",
             "openai.api_key",
             "openai.model",
             "openai.proxy",
             "openai.retries",
+            "output.candidate_bodies",
+            "output.candidates",
             "output.conventional_commit",
             "output.conventional_commit_prefix_format",
             "output.lang",
             "output.show_per_file_summary",
+            "prompt.changelog_summary",
             "prompt.commit_summary",
+            "prompt.commit_template",
             "prompt.commit_title",
             "prompt.conventional_commit_prefix",
             "prompt.file_diff",
+            "prompt.file_group",
+            "prompt.file_review",
+            "prompt.merge_summary",
+            "prompt.message_check",
+            "prompt.pr_description",
+            "prompt.pr_title",
+            "prompt.squash_summary",
             "prompt.translation",
+            "redaction.action",
+            "redaction.enabled",
+            "redaction.patterns",
+            "review.fail_on",
+            "summarization.file_error_policy",
+            "summarization.file_order",
+            "summarization.generated_files",
+            "summarization.summary_points_token_limit",
+            "template_mode",
         ]
     }
     #[test]
@@ -23,16 +23,73 @@ impl SplitPrefixInclusive for str {
     }
 }
 
-/// Finds the file name from a diff. The diff is expected to be of the form
-/// "diff --git a/file_name b/file_name".
+/// Ranks how central a file usually is to a change: source code first, then
+/// tests, docs and finally build or config files.
+pub(crate) fn get_file_importance(file_name: &str) -> u8 {
+    let lower = file_name.to_lowercase();
+    let base_name = lower.rsplit('/').next().unwrap_or(&lower);
+    let extension = base_name.rsplit_once('.').map(|(_, ext)| ext);
+    if lower
+        .split('/')
+        .any(|part| matches!(part, "test" | "tests" | "spec" | "__tests__"))
+        || base_name.contains("_test.")
+        || base_name.contains(".test.")
+        || base_name.contains(".spec.")
+        || base_name.starts_with("test_")
+    {
+        1
+    } else if lower.split('/').any(|part| matches!(part, "doc" | "docs"))
+        || matches!(extension, Some("md" | "rst" | "txt" | "adoc"))
+    {
+        2
+    } else if base_name.starts_with('.')
+        || matches!(
+            extension,
+            Some("toml" | "json" | "yaml" | "yml" | "lock" | "ini" | "cfg" | "xml")
+        )
+    {
+        3
+    } else {
+        0
+    }
+}
+
+/// Approximates the number of tokens in `text` using the cl100k_base encoding.
+pub(crate) fn count_tokens(text: &str) -> usize {
+    tiktoken_rs::cl100k_base_singleton()
+        .lock()
+        .encode_with_special_tokens(text)
+        .len()
+}
+
+/// Returns the directory group a path belongs to, one level up.
 ///
-/// If the diff is not of the expected form, then None is returned.
-pub(crate) fn get_file_name_from_diff(file_diff: &str) -> Option<&str> {
-    let (_, suffix) = file_diff.split_once("diff --git ")?;
-    let mut parts = suffix.split_whitespace();
-    let _old = parts.next()?;
-    let new = parts.next()?;
-    new.strip_prefix("b/")
+/// Groups are directory paths with a trailing `/`; `./` is the repository root.
+pub(crate) fn get_parent_group(path: &str) -> String {
+    match path.trim_end_matches('/').rsplit_once('/') {
+        Some((dir, _)) => format!("{dir}/"),
+        None => "./".to_string(),
+    }
+}
+
+/// Formats a Unix timestamp as an ISO 8601 date (`YYYY-MM-DD`) in UTC.
+pub(crate) fn format_date(timestamp: u64) -> String {
+    // civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
 }
 
 #[cfg(test)]
@@ -67,32 +124,30 @@ mod tests {
             &["x111", "\nx222", "\nx333"]
         );
     }
+
+    #[test]
+    fn test_get_file_importance() {
+        assert_eq!(get_file_importance("src/main.rs"), 0);
+        assert_eq!(get_file_importance("tests/e2e/test_version.sh"), 1);
+        assert_eq!(get_file_importance("src/app.spec.ts"), 1);
+        assert_eq!(get_file_importance("README.md"), 2);
+        assert_eq!(get_file_importance("Cargo.toml"), 3);
+        assert_eq!(get_file_importance(".github/workflows/ci.yml"), 3);
+    }
+
     #[test]
-    fn test_get_file_name_from_diff() {
-        assert_eq!(get_file_name_from_diff(""), None);
-        assert_eq!(get_file_name_from_diff("asdasdas"), None);
-        assert_eq!(get_file_name_from_diff("diff --git a/"), None);
-        assert_eq!(get_file_name_from_diff("diff --git b/"), None);
-        assert_eq!(
-            get_file_name_from_diff(
-                &r#"
-diff --git a/foo b/foo
-new file mode 100644
-index 0000000..a51b2a6
---- /dev/null
-+++ b/foo
-@@ -0,0 +1 @@
-+sadasdas
-"#[1..]
-            ),
-            Some("foo")
-        );
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_792_454_399), "2026-10-19");
+    }
 
-        assert_eq!(
-            get_file_name_from_diff(
-                "diff --git a/old_name b/new_name\n--- a/old_name\n+++ b/new_name"
-            ),
-            Some("new_name")
-        );
+    #[test]
+    fn test_get_parent_group() {
+        assert_eq!(get_parent_group("src/llms/openai.rs"), "src/llms/");
+        assert_eq!(get_parent_group("src/llms/"), "src/");
+        assert_eq!(get_parent_group("src/"), "./");
+        assert_eq!(get_parent_group("README.md"), "./");
+        assert_eq!(get_parent_group("./"), "./");
     }
 }
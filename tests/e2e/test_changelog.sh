@@ -0,0 +1,76 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    git commit --allow-empty -m "feat: Initial release"
+
+    # there is no tag to start from yet
+    if gptcommit changelog 2> "${TEMPDIR}/.git/error.log"; then
+        exit 1
+    fi
+    grep -q "No tag found before HEAD" "${TEMPDIR}/.git/error.log"
+
+    # errors reading the repository are not mistaken for a missing tag
+    if gptcommit changelog --to nope 2> "${TEMPDIR}/.git/error.log"; then
+        exit 1
+    fi
+    if grep -q "No tag found" "${TEMPDIR}/.git/error.log"; then
+        exit 1
+    fi
+
+    git tag v1.0.0
+    git commit --allow-empty -m "feat: Add a flag"
+    git commit --allow-empty -m "fix(parser): Handle empty input"
+    git commit --allow-empty -m "Tweak wording"
+    git commit --allow-empty -m "fixup! Tweak wording"
+    git tag v1.1.0
+
+    # the release tag itself is the end of the range
+    for backend in gix subprocess; do
+        GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit changelog --release 1.1.0 > "${TEMPDIR}/.git/${backend}.md"
+    done
+    cmp "${TEMPDIR}/.git/gix.md" "${TEMPDIR}/.git/subprocess.md"
+    head -1 "${TEMPDIR}/.git/gix.md" | grep -q "^## \[1.1.0\] - [0-9-]*$"
+    grep -q "^### Added$" "${TEMPDIR}/.git/gix.md"
+    grep -q "^- Add a flag$" "${TEMPDIR}/.git/gix.md"
+    grep -q "^- \*\*parser:\*\* Handle empty input$" "${TEMPDIR}/.git/gix.md"
+    grep -q "^- Tweak wording$" "${TEMPDIR}/.git/gix.md"
+    if grep -q "Initial release\|fixup!" "${TEMPDIR}/.git/gix.md"; then
+        exit 1
+    fi
+
+    # sections can be summarized by the model
+    GPTCOMMIT__MODEL_PROVIDER="tester-foobar" gptcommit changelog --from v1.0.0 --summarize > "${TEMPDIR}/.git/summary.md"
+    head -1 "${TEMPDIR}/.git/summary.md" | grep -q "^## \[Unreleased\]$"
+    grep -q "^foo bar$" "${TEMPDIR}/.git/summary.md"
+    GPTCOMMIT__MODEL_PROVIDER="tester-foobar" gptcommit changelog --from v1.0.0 --summarize --format json > "${TEMPDIR}/.git/summary.json"
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert output["schema_version"] == 1
+assert output["title"] == "## [Unreleased]"
+assert "\nfoo bar\n" in output["message"]
+assert output["usage"]["requests"] == 3
+' "${TEMPDIR}/.git/summary.json"
+    # without summaries the model is not asked
+    gptcommit changelog --from v1.0.0 --format json > "${TEMPDIR}/.git/changelog.json"
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert "- Add a flag" in output["message"]
+assert output["usage"]["requests"] == 0
+' "${TEMPDIR}/.git/changelog.json"
+
+    # the template can be replaced
+    GPTCOMMIT__CHANGELOG__TEMPLATE='{% for section in sections %}{{ section.name }}: {{ section.entries | length }}
+{% endfor %}' gptcommit changelog --output "${TEMPDIR}/.git/custom.txt"
+    test "$(cat "${TEMPDIR}/.git/custom.txt")" = "Added: 1
+Fixed: 1
+Other: 1"
+)
+rm -rf "${TEMPDIR}"
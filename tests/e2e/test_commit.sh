@@ -0,0 +1,50 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    # nothing is staged yet
+    if gptcommit commit --yes; then
+        exit 1
+    fi
+
+    # aborting leaves the changes staged
+    printf 'one\n' > one.txt
+    git add one.txt
+    echo a | gptcommit commit
+    if git rev-parse --verify --quiet HEAD; then
+        exit 1
+    fi
+
+    # accepting commits the generated message, passing extra args to git
+    printf 'r\ny\n' | gptcommit commit --signoff
+    test "$(git log -1 --format=%s)" = "foo bar"
+    git log -1 --format=%b | grep -q "^Signed-off-by: Test <test@example.com>$"
+
+    # the message can be edited before committing
+    printf 'two\n' > two.txt
+    git add two.txt
+    printf 'e\ny\n' | GIT_EDITOR="edit() { echo \"\$1\" > .git/edited; sed -i -e 's/^foo bar$/Add two/' \"\$1\"; }; edit" gptcommit commit --no-verify
+    test "$(git log -1 --format=%s)" = "Add two"
+    # the message is edited inside the git directory and removed afterwards
+    test "$(basename "$(cat .git/edited)")" = "GPTCOMMIT_EDITMSG"
+    test "$(cd "$(dirname "$(cat .git/edited)")" && pwd -P)" = "$(cd "$(git rev-parse --absolute-git-dir)" && pwd -P)"
+    test ! -e .git/GPTCOMMIT_EDITMSG
+    if git log -1 --format=%B | grep -q "^#"; then
+        exit 1
+    fi
+
+    # identical candidates are offered only once
+    printf 'three\n' > three.txt
+    git add three.txt
+    gptcommit commit --candidates 3 --yes
+    test "$(git log -1 --format=%s)" = "foo bar"
+)
+rm -rf "${TEMPDIR}"
@@ -0,0 +1,45 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    printf 'def f():\n    return 1\n' > a.py
+    printf 'one\n' > m.txt
+    printf 'one\n' > z.txt
+    git add .
+    git commit -m "initial"
+
+    # a whitespace-only change is only seen by the override that keeps whitespace
+    printf 'def f():\n  return 1\n' > a.py
+    printf 'one\ntwo\n' > m.txt
+    printf 'one\ntwo\n' > z.txt
+    git add .
+    printf '[[diff.overrides]]\npath = "*.py"\nwhitespace = "keep"\n' > .git/gptcommit.toml
+
+    # overridden files keep their place in git's diff order
+    printf 'z.txt\na.py\nm.txt\n' > .git/order
+    git config diff.orderFile .git/order
+    mkdir sub
+    cd sub
+    GPTCOMMIT__GIT__BACKEND=subprocess gptcommit summarize --staged --format json > "${TEMPDIR}/.git/subprocess.json"
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert [file["file_name"] for file in output["file_summaries"]] == ["z.txt", "a.py", "m.txt"]
+' "${TEMPDIR}/.git/subprocess.json"
+
+    # gix doesn't read diff.orderFile, and keeps path order
+    GPTCOMMIT__GIT__BACKEND=gix gptcommit summarize --staged --format json > "${TEMPDIR}/.git/gix.json"
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert [file["file_name"] for file in output["file_summaries"]] == ["a.py", "m.txt", "z.txt"]
+' "${TEMPDIR}/.git/gix.json"
+)
+rm -rf "${TEMPDIR}"
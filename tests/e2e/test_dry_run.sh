@@ -0,0 +1,74 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'fn main() {}\n' > main.rs
+    git add main.rs
+
+    # nothing is sent, so no API key is needed
+    unset OPENAI_API_KEY
+    export GPTCOMMIT__OPENAI__API_KEY=""
+
+    gptcommit commit --dry-run > "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^=== prompt.file_diff: ~[0-9]* tokens for gpt-4.1-nano ===$" "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^=== prompt.commit_title: " "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^=== prompt.commit_summary: " "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^=== prompt.conventional_commit_prefix: " "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^+fn main() {}$" "${TEMPDIR}/.git/dry-run.txt"
+    if git rev-parse --verify -q HEAD; then
+        exit 1
+    fi
+
+    # the hook leaves the message alone, and the translation prompt is shown
+    printf 'kept\n' > "${TEMPDIR}/.git/msg"
+    GPTCOMMIT__DRY_RUN=true GPTCOMMIT__OUTPUT__LANG=ja \
+    gptcommit prepare-commit-msg \
+      --commit-msg-file "${TEMPDIR}/.git/msg" \
+      --commit-source "" > "${TEMPDIR}/.git/dry-run-hook.txt"
+    grep -q "^=== prompt.translation: " "${TEMPDIR}/.git/dry-run-hook.txt"
+    test "$(cat "${TEMPDIR}/.git/msg")" = "kept"
+
+    # the other actions only print their prompts, which quote the placeholder
+    # file summaries, never placeholder results
+    no_results() {
+        if grep -v "^\[file_diff completion\]$" "$1" | grep "completion\]\|No findings"; then
+            exit 1
+        fi
+    }
+    gptcommit review --dry-run > "${TEMPDIR}/.git/dry-run-review.txt"
+    grep "^=== prompt.file_review: " "${TEMPDIR}/.git/dry-run-review.txt" > /dev/null
+    no_results "${TEMPDIR}/.git/dry-run-review.txt"
+
+    gptcommit summarize --staged --dry-run > "${TEMPDIR}/.git/dry-run-summarize.txt"
+    grep "^=== prompt.commit_title: " "${TEMPDIR}/.git/dry-run-summarize.txt" > /dev/null
+    no_results "${TEMPDIR}/.git/dry-run-summarize.txt"
+
+    git commit -q -m "add main"
+    git tag v0.1.0
+    git checkout -q -b feature
+    printf 'fn main() { run(); }\n' > main.rs
+    git commit -q -a -m "feat: run"
+
+    gptcommit explain HEAD --compare --dry-run > "${TEMPDIR}/.git/dry-run-explain.txt"
+    grep "^=== prompt.message_check: " "${TEMPDIR}/.git/dry-run-explain.txt" > /dev/null
+    no_results "${TEMPDIR}/.git/dry-run-explain.txt"
+
+    gptcommit pr --base main --dry-run > "${TEMPDIR}/.git/dry-run-pr.txt"
+    grep "^=== prompt.pr_title: " "${TEMPDIR}/.git/dry-run-pr.txt" > /dev/null
+    no_results "${TEMPDIR}/.git/dry-run-pr.txt"
+
+    gptcommit changelog --summarize --dry-run -o "${TEMPDIR}/.git/CHANGELOG.md" > "${TEMPDIR}/.git/dry-run-changelog.txt"
+    grep "^=== prompt.changelog_summary: " "${TEMPDIR}/.git/dry-run-changelog.txt" > /dev/null
+    test ! -e "${TEMPDIR}/.git/CHANGELOG.md"
+
+    gptcommit reword --base main --dry-run > "${TEMPDIR}/.git/dry-run-reword.txt"
+    grep "^=== prompt.commit_title: " "${TEMPDIR}/.git/dry-run-reword.txt" > /dev/null
+    no_results "${TEMPDIR}/.git/dry-run-reword.txt"
+    test "$(git log -1 --format=%s)" = "feat: run"
+)
+rm -rf "${TEMPDIR}"
@@ -0,0 +1,65 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'fn main() {}\n' > main.rs
+    git add main.rs
+    git commit -m "initial"
+
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    # the root commit is compared with the empty tree
+    for backend in gix subprocess; do
+        GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit explain --compare HEAD > "${TEMPDIR}/.git/root-${backend}.txt"
+    done
+    cmp "${TEMPDIR}/.git/root-gix.txt" "${TEMPDIR}/.git/root-subprocess.txt"
+    grep -q "^\[main.rs\]$" "${TEMPDIR}/.git/root-gix.txt"
+    grep -q "may not match" "${TEMPDIR}/.git/root-gix.txt"
+
+    printf 'fn main() {\n    println!("hi");\n}\n' > main.rs
+    git commit -am "Print a greeting"
+    printf 'new\n' > added.txt
+    git add added.txt
+    git commit -m "Add a file"
+
+    for backend in gix subprocess; do
+        GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit explain HEAD > "${TEMPDIR}/.git/explain-${backend}.txt"
+    done
+    cmp "${TEMPDIR}/.git/explain-gix.txt" "${TEMPDIR}/.git/explain-subprocess.txt"
+    grep -q "^\[added.txt\]$" "${TEMPDIR}/.git/explain-gix.txt"
+    if grep -q "main.rs" "${TEMPDIR}/.git/explain-gix.txt"; then
+        exit 1
+    fi
+    if grep -q "recorded messages" "${TEMPDIR}/.git/explain-gix.txt"; then
+        exit 1
+    fi
+
+    # a range covers the files of all its commits
+    gptcommit explain HEAD~2..HEAD > "${TEMPDIR}/.git/explain-range.txt"
+    grep -q "^\[main.rs\]$" "${TEMPDIR}/.git/explain-range.txt"
+    grep -q "^\[added.txt\]$" "${TEMPDIR}/.git/explain-range.txt"
+
+    # the test model disagrees with every message
+    gptcommit explain --compare HEAD~2...HEAD > "${TEMPDIR}/.git/explain-compare.txt"
+    grep -q "may not match" "${TEMPDIR}/.git/explain-compare.txt"
+    grep -q "^- foo bar$" "${TEMPDIR}/.git/explain-compare.txt"
+    gptcommit explain --compare --format json HEAD~2...HEAD > "${TEMPDIR}/.git/explain.json"
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert output["schema_version"] == 1
+assert output["message"] == "foo bar\n\nThe recorded messages may not match the changes:\n- foo bar"
+assert [file["file_name"] for file in output["file_summaries"]] == ["added.txt", "main.rs"]
+' "${TEMPDIR}/.git/explain.json"
+
+    git commit --allow-empty -m "Nothing"
+    if gptcommit explain HEAD; then
+        exit 1
+    fi
+)
+rm -rf "${TEMPDIR}"
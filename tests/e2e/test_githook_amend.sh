@@ -0,0 +1,57 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+export MSGDIR=$(mktemp -d)
+
+run_hook() {
+    echo "" > "$1"
+    GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+    GPTCOMMIT__ALLOW_AMEND="true" \
+    GPTCOMMIT__OUTPUT__SHOW_PER_FILE_SUMMARY="true" \
+    GPTCOMMIT__GIT__BACKEND="$2" \
+    gptcommit prepare-commit-msg \
+      --commit-msg-file "$1" \
+      --commit-source "commit" \
+      --commit-sha "$3"
+}
+
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'root\n' > root.txt
+    git add root.txt
+    git commit --no-verify -m "root"
+
+    for backend in gix subprocess; do
+        # amending the root commit diffs against the empty tree
+        run_hook "${MSGDIR}/${backend}-root.txt" "${backend}" HEAD
+        grep -q "\[root.txt\]" "${MSGDIR}/${backend}-root.txt"
+    done
+
+    printf 'first\n' > first.txt
+    git add first.txt
+    git commit --no-verify -m "first"
+    printf 'second\n' > second.txt
+    git add second.txt
+
+    for backend in gix subprocess; do
+        # the amended commit includes first.txt as well as the staged second.txt
+        run_hook "${MSGDIR}/${backend}-amend.txt" "${backend}" HEAD
+        grep -q "\[first.txt\]" "${MSGDIR}/${backend}-amend.txt"
+        grep -q "\[second.txt\]" "${MSGDIR}/${backend}-amend.txt"
+        if grep -q "\[root.txt\]" "${MSGDIR}/${backend}-amend.txt"; then
+            exit 1
+        fi
+
+        # reusing the message of another commit with -c keeps diffing against HEAD
+        run_hook "${MSGDIR}/${backend}-reuse.txt" "${backend}" HEAD~1
+        grep -q "\[second.txt\]" "${MSGDIR}/${backend}-reuse.txt"
+        if grep -q "\[first.txt\]" "${MSGDIR}/${backend}-reuse.txt"; then
+            exit 1
+        fi
+    done
+)
+rm -rf "${TEMPDIR}" "${MSGDIR}"
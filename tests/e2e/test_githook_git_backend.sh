@@ -0,0 +1,74 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+export MSGDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'fn main() {}\n' > main.rs
+    printf 'old\n' > notes.txt
+    git add main.rs notes.txt
+    git commit --no-verify -m "initial"
+
+    printf 'fn main() {\n    println!("hi");\n}\n' > main.rs
+    git rm -q notes.txt
+    printf 'new\n' > added.txt
+    git add main.rs added.txt
+
+    # both backends see the same staged changes
+    for backend in gix subprocess; do
+        echo "" > "${MSGDIR}/${backend}.txt"
+        GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+        GPTCOMMIT__OUTPUT__SHOW_PER_FILE_SUMMARY="true" \
+        GPTCOMMIT__GIT__BACKEND="${backend}" \
+        gptcommit prepare-commit-msg \
+          --commit-msg-file "${MSGDIR}/${backend}.txt" \
+          --commit-source ""
+    done
+
+    grep -q "\[main.rs\]" "${MSGDIR}/gix.txt"
+    grep -q "Delete notes.txt" "${MSGDIR}/gix.txt"
+    cmp "${MSGDIR}/gix.txt" "${MSGDIR}/subprocess.txt"
+
+    # both backends write the same diffs, with function context, hunk headings
+    # and missing newlines, even from a subdirectory
+    git reset -q
+    printf 'fn one() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n    let d = 4;\n    run(a);\n}\n\nfn two() {\n    let x = 1;\n    let y = 2;\n    let z = 3;\n    let w = 4;\n    run(x);\n}\n' > lib.rs
+    printf 'no newline' > raw.txt
+    git add lib.rs raw.txt
+    git commit --no-verify -q -m "add lib"
+    sed 's/run(x)/walk(x)/' lib.rs > lib.rs.new
+    mv lib.rs.new lib.rs
+    printf 'still no newline' > raw.txt
+    printf '' > empty.txt
+    git add lib.rs raw.txt empty.txt
+    mkdir -p sub
+    for backend in gix subprocess; do
+        (
+            cd sub
+            GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit summarize --staged --dry-run
+        ) | sort > "${MSGDIR}/${backend}-diff.txt"
+        GPTCOMMIT__DIFF__FUNCTION_CONTEXT=false GPTCOMMIT__GIT__BACKEND="${backend}" \
+        gptcommit summarize --staged --dry-run | sort > "${MSGDIR}/${backend}-hunks.txt"
+    done
+    grep "^ fn two() {$" "${MSGDIR}/gix-diff.txt" > /dev/null
+    grep "^\\\\ No newline at end of file$" "${MSGDIR}/gix-diff.txt" > /dev/null
+    grep "^@@ -11,5 +11,5 @@ fn two() {$" "${MSGDIR}/gix-hunks.txt" > /dev/null
+    cmp "${MSGDIR}/gix-diff.txt" "${MSGDIR}/subprocess-diff.txt"
+    cmp "${MSGDIR}/gix-hunks.txt" "${MSGDIR}/subprocess-hunks.txt"
+    git reset -q
+
+    # `git commit -a` passes a temporary index to the hook
+    gptcommit install
+    GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+    GPTCOMMIT__GIT__BACKEND="gix" \
+    GPTCOMMIT__OUTPUT__SHOW_PER_FILE_SUMMARY="true" \
+    GIT_EDITOR=true \
+    git commit -a
+
+    git log -1 --format=%B | grep -q "\[main.rs\]"
+)
+rm -rf "${TEMPDIR}" "${MSGDIR}"
@@ -0,0 +1,40 @@
+#!/bin/sh
+set -eu
+
+DIFF_CONTENT_PATH="$(pwd)/tests/data/example_1.diff"
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    printf 'src/** gptcommit=skip\n' > .gitattributes
+    git add .gitattributes
+
+    export TEMPFILE=$(mktemp)
+    echo "foo" > $TEMPFILE
+
+    GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+    gptcommit prepare-commit-msg \
+      --git-diff-content "${DIFF_CONTENT_PATH}" \
+      --commit-msg-file "${TEMPFILE}" \
+      --commit-source "" 2>&1 | tee "${TEMPDIR}/output.log"
+
+    grep -q "skipping src/main.rs due to gitattributes" "${TEMPDIR}/output.log"
+
+    # paths are relative to the repository root, also from a subdirectory
+    mkdir sub
+    (
+        cd sub
+        GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+        GPTCOMMIT__GIT__BACKEND="subprocess" \
+        gptcommit prepare-commit-msg \
+          --git-diff-content "${DIFF_CONTENT_PATH}" \
+          --commit-msg-file "${TEMPFILE}" \
+          --commit-source "" 2>&1 | tee "${TEMPDIR}/output.log"
+    )
+
+    grep -q "skipping src/main.rs due to gitattributes" "${TEMPDIR}/output.log"
+
+    cat $TEMPFILE
+)
+rm -rf "${TEMPDIR}"
@@ -0,0 +1,28 @@
+#!/bin/sh
+set -eu
+
+DIFF_CONTENT_PATH="$(pwd)/tests/data/example_1.diff"
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    printf 'src/\n.vscode/*\n!.vscode/launch.json\n' > .gptcommitignore
+
+    export TEMPFILE=$(mktemp)
+    echo "foo" > $TEMPFILE
+
+    GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+    gptcommit prepare-commit-msg \
+      --git-diff-content "${DIFF_CONTENT_PATH}" \
+      --commit-msg-file "${TEMPFILE}" \
+      --commit-source "" 2>&1 | tee "${TEMPDIR}/output.log"
+
+    grep -q "skipping src/main.rs" "${TEMPDIR}/output.log"
+    if grep -q "skipping .vscode/launch.json" "${TEMPDIR}/output.log"; then
+        exit 1
+    fi
+
+    cat $TEMPFILE
+)
+rm -rf "${TEMPDIR}"
@@ -0,0 +1,65 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'x\n' > conflict.txt
+    git add conflict.txt
+    git commit --no-verify -m "initial"
+
+    git checkout -b feature
+    printf 'feature\n' > feature.txt
+    git add feature.txt
+    git commit --no-verify -m "Add feature file"
+    printf 'y\n' > conflict.txt
+    git commit --no-verify -am "Change conflict to y"
+
+    git checkout main
+    printf 'z\n' > conflict.txt
+    git commit --no-verify -am "Change conflict to z"
+
+    gptcommit install
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+    export GPTCOMMIT__OUTPUT__SHOW_PER_FILE_SUMMARY="true"
+
+    for backend in gix subprocess; do
+        export GPTCOMMIT__GIT__BACKEND="${backend}"
+
+        # merges are only summarized when allowed
+        git merge --no-edit feature || true
+        printf 'yz\n' > conflict.txt
+        git add conflict.txt
+        git commit --no-edit > "${TEMPDIR}/output.log" 2>&1
+        grep -q "allow_merge" "${TEMPDIR}/output.log"
+        git log -1 --format=%B | grep -q "^Merge branch 'feature'"
+        git reset -q --hard HEAD~1
+
+        # conflict resolutions are summarized under the merge title
+        git merge --no-edit feature || true
+        printf 'yz\n' > conflict.txt
+        git add conflict.txt
+        GPTCOMMIT__ALLOW_MERGE="true" git commit --no-edit
+        git log -1 --format=%B | head -n 1 | grep -q "^Merge branch 'feature'$"
+        git log -1 --format=%B | grep -q "\[conflict.txt\]"
+        if git log -1 --format=%B | grep -q "\[feature.txt\]"; then
+            exit 1
+        fi
+        git reset -q --hard HEAD~1
+
+        # squashed commits are summarized from their combined diff
+        git merge --squash feature || true
+        printf 'yz\n' > conflict.txt
+        git add conflict.txt
+        GPTCOMMIT__ALLOW_SQUASH="true" git commit --no-edit
+        git log -1 --format=%B | grep -q "\[feature.txt\]"
+        if git log -1 --format=%B | grep -q "Squashed commit of the following"; then
+            exit 1
+        fi
+        git reset -q --hard HEAD~1
+    done
+)
+rm -rf "${TEMPDIR}"
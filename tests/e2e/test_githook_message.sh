@@ -0,0 +1,38 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+
+    gptcommit install
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    # messages given with -m are left alone by default
+    printf 'one\n' > one.txt
+    git add one.txt
+    git commit -m "wip"
+    test "$(git log -1 --format=%B)" = "wip"
+
+    # the generated body is added below the whole message
+    printf 'two\n' > two.txt
+    git add two.txt
+    GPTCOMMIT__MESSAGE_MODE="append" git commit -m "Fix the parser" -m "Keep this note"
+    test "$(git log -1 --format=%s)" = "Fix the parser"
+    git log -1 --format=%b | grep -q "^Keep this note$"
+    git log -1 --format=%b | grep -q "^foo bar$"
+
+    # the subject line is expanded into a generated body
+    printf 'three\n' > three.txt
+    git add three.txt
+    GPTCOMMIT__MESSAGE_MODE="expand" git commit -m "Fix the lexer" -m "Drop this note"
+    test "$(git log -1 --format=%s)" = "Fix the lexer"
+    git log -1 --format=%b | grep -q "^foo bar$"
+    if git log -1 --format=%b | grep -q "Drop this note"; then
+        exit 1
+    fi
+)
+rm -rf "${TEMPDIR}"
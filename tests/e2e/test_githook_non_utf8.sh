@@ -0,0 +1,32 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+export MSGDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf '/* d\351but */\n' > latin1.c
+    git add latin1.c
+    git commit --no-verify -m "initial"
+
+    printf '/* fin de l\047\351t\351 */\n' > latin1.c
+    printf '\001\002\377\376\003\n' > data.bin
+    git add latin1.c data.bin
+
+    for backend in gix subprocess; do
+        echo "" > "${MSGDIR}/${backend}.txt"
+        GPTCOMMIT__MODEL_PROVIDER="tester-foobar" \
+        GPTCOMMIT__OUTPUT__SHOW_PER_FILE_SUMMARY="true" \
+        GPTCOMMIT__GIT__BACKEND="${backend}" \
+        gptcommit prepare-commit-msg \
+          --commit-msg-file "${MSGDIR}/${backend}.txt" \
+          --commit-source ""
+
+        grep -q "\[latin1.c\]" "${MSGDIR}/${backend}.txt"
+        grep -q "Add binary asset data.bin" "${MSGDIR}/${backend}.txt"
+    done
+)
+rm -rf "${TEMPDIR}" "${MSGDIR}"
@@ -0,0 +1,40 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf '{{ title }}\n\nWhy:\n{{ body }}\n\nTesting:\n# Describe how the change was tested\n' > "${TEMPDIR}/.gitmessage"
+    git config commit.template "${TEMPDIR}/.gitmessage"
+
+    gptcommit install
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+    export GPTCOMMIT__OUTPUT__CONVENTIONAL_COMMIT="false"
+    export GIT_EDITOR=true
+
+    # templates are left alone by default, so git aborts the unedited commit
+    printf 'one\n' > one.txt
+    git add one.txt
+    if git commit; then
+        exit 1
+    fi
+
+    # the generated title and body replace the placeholders
+    GPTCOMMIT__TEMPLATE_MODE="placeholders" git commit
+    test "$(git log -1 --format=%s)" = "foo bar"
+    git log -1 --format=%B | grep -q "^Why:$"
+    git log -1 --format=%B | grep -q "^Testing:$"
+    if git log -1 --format=%B | grep -q "{{"; then
+        exit 1
+    fi
+
+    # the model fills in the template
+    printf 'two\n' > two.txt
+    git add two.txt
+    GPTCOMMIT__TEMPLATE_MODE="prompt" git commit
+    test "$(git log -1 --format=%B)" = "foo bar"
+)
+rm -rf "${TEMPDIR}"
@@ -38,3 +38,24 @@ export TEMPDIR=$(mktemp -d)
     set -e
 )
 rm -rf "${TEMPDIR}"
+
+#############################
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init
+    # an existing pre-commit hook is left alone by install --review
+    printf '#!/bin/sh\nexit 0\n' > .git/hooks/pre-commit
+    if gptcommit install --review 2> err.txt; then exit 1; fi
+    grep "is not gptcommit's pre-commit hook" err.txt > /dev/null
+    test "$(cat .git/hooks/pre-commit)" = "$(printf '#!/bin/sh\nexit 0')"
+    test ! -e .git/hooks/prepare-commit-msg
+
+    rm .git/hooks/pre-commit
+    gptcommit install --review
+    # reinstalling over our own hook is fine
+    gptcommit install --review
+    grep "gptcommit review" .git/hooks/pre-commit > /dev/null
+)
+rm -rf "${TEMPDIR}"
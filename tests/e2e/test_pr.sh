@@ -0,0 +1,67 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+export ORIGINDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'fn main() {}\n' > main.rs
+    git add main.rs
+    git commit -m "initial"
+
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+    export GPTCOMMIT__OUTPUT__SHOW_PER_FILE_SUMMARY="true"
+
+    # nothing to describe on the default branch itself
+    if gptcommit pr; then
+        exit 1
+    fi
+
+    git checkout -b feature
+    printf 'fn main() {\n    println!("hi");\n}\n' > main.rs
+    git commit -am "Print a greeting"
+    printf 'new\n' > added.txt
+    git add added.txt
+    git commit -m "Add a file"
+
+    # later commits on main are not part of the pull request
+    git checkout main
+    printf 'other\n' > other.txt
+    git add other.txt
+    git commit -m "Unrelated change"
+    git checkout feature
+
+    # the base defaults to the local main branch without a remote
+    for backend in gix subprocess; do
+        GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit pr > "${TEMPDIR}/.git/pr-${backend}.md"
+    done
+    cmp "${TEMPDIR}/.git/pr-gix.md" "${TEMPDIR}/.git/pr-subprocess.md"
+    head -1 "${TEMPDIR}/.git/pr-gix.md" | grep -q "^foo bar$"
+    grep -q "^\[main.rs\]$" "${TEMPDIR}/.git/pr-gix.md"
+    grep -q "^\[added.txt\]$" "${TEMPDIR}/.git/pr-gix.md"
+    if grep -q "other.txt" "${TEMPDIR}/.git/pr-gix.md"; then
+        exit 1
+    fi
+
+    # the default branch of the remote is preferred, and the output can go to a file
+    git init --bare "${ORIGINDIR}"
+    git remote add origin "${ORIGINDIR}"
+    git push -q origin main
+    git remote set-head origin main
+    git branch -q -D main
+    gptcommit pr --output "${TEMPDIR}/.git/pr.md"
+    cmp "${TEMPDIR}/.git/pr-gix.md" "${TEMPDIR}/.git/pr.md"
+
+    gptcommit pr --base HEAD~1 > "${TEMPDIR}/.git/pr-last.md"
+
+    gptcommit pr --format json > "${TEMPDIR}/.git/pr.json"
+    grep -q '"schema_version": 1,' "${TEMPDIR}/.git/pr.json"
+    grep -q '"file_name": "added.txt",' "${TEMPDIR}/.git/pr.json"
+    if grep -q "main.rs" "${TEMPDIR}/.git/pr-last.md"; then
+        exit 1
+    fi
+)
+rm -rf "${TEMPDIR}" "${ORIGINDIR}"
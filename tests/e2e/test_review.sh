@@ -0,0 +1,67 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'fn main() {}\n' > main.rs
+    printf 'old\n' > old.txt
+    git add main.rs old.txt
+    git commit -m "initial"
+
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    # nothing staged, nothing to review
+    gptcommit review > "${TEMPDIR}/.git/review-empty.txt"
+    grep -q "no staged changes" "${TEMPDIR}/.git/review-empty.txt"
+
+    printf 'fn main() {\n    dbg!(1);\n}\n' > main.rs
+    git rm -q old.txt
+    git add main.rs
+
+    # the test model's answer is kept as a low severity finding about the file,
+    # deleted files are not reviewed
+    for backend in gix subprocess; do
+        GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit review > "${TEMPDIR}/.git/review-${backend}.txt"
+    done
+    cmp "${TEMPDIR}/.git/review-gix.txt" "${TEMPDIR}/.git/review-subprocess.txt"
+    grep -q "^main.rs: low: foo bar$" "${TEMPDIR}/.git/review-gix.txt"
+    if grep -q "old.txt" "${TEMPDIR}/.git/review-gix.txt"; then
+        exit 1
+    fi
+
+    if gptcommit review --fail-on low; then
+        exit 1
+    fi
+    if gptcommit review --fail-on critical; then
+        exit 1
+    fi
+    gptcommit review --fail-on never
+
+    # the findings are printed as JSON before the review fails
+    if gptcommit review --fail-on low --format json > "${TEMPDIR}/.git/review.json"; then
+        exit 1
+    fi
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert output["schema_version"] == 1
+assert output["message"] == "main.rs: low: foo bar"
+' "${TEMPDIR}/.git/review.json"
+
+    # the pre-commit hook blocks the commit unless it is skipped
+    gptcommit install --review
+    test -x .git/hooks/pre-commit
+    gptcommit config set --local review.fail_on low
+    if git commit -m "Debug main"; then
+        exit 1
+    fi
+    git commit --no-verify -m "Debug main"
+
+    gptcommit uninstall
+    test ! -e .git/hooks/pre-commit
+)
+rm -rf "${TEMPDIR}"
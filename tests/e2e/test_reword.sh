@@ -0,0 +1,98 @@
+#!/bin/sh
+set -eu
+
+export TEMPDIR=$(mktemp -d)
+export ORIGINDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'one\n' > one.txt
+    git add one.txt
+    git commit -m "initial"
+    git init --bare "${ORIGINDIR}"
+    git remote add origin "${ORIGINDIR}"
+    git push -q origin main
+
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    git checkout -b feature
+    printf 'two\n' > two.txt
+    git add two.txt
+    GIT_AUTHOR_NAME="Someone Else" GIT_AUTHOR_DATE="2020-01-01T00:00:00Z" git commit -m "wip"
+    printf 'three\n' > three.txt
+    git add three.txt
+    git commit -s -m "fix" -m "Change-Id: I0123456789"
+    printf 'uncommitted\n' > one.txt
+    original_head=$(git rev-parse HEAD)
+
+    # a preview only prints the old and new messages
+    gptcommit reword --preview > "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^- wip$" "${TEMPDIR}/.git/dry-run.txt"
+    grep -q "^+ foo bar$" "${TEMPDIR}/.git/dry-run.txt"
+    test "$(git rev-parse HEAD)" = "${original_head}"
+
+    # declining leaves the commits alone
+    echo n | gptcommit reword
+    test "$(git rev-parse HEAD)" = "${original_head}"
+
+    echo y | GPTCOMMIT__GIT__BACKEND="gix" gptcommit reword
+    test "$(git log -1 --format=%s HEAD~1)" = "foo bar"
+    test "$(git log -1 --format=%s HEAD)" = "foo bar"
+    test "$(git rev-parse HEAD^{tree})" = "$(git rev-parse ${original_head}^{tree})"
+    test "$(git log -1 --format=%an HEAD~1)" = "Someone Else"
+    test "$(git log -1 --format=%ad --date=short HEAD~1)" = "2020-01-01"
+    test "$(git rev-parse HEAD~2)" = "$(git rev-parse origin/main)"
+    test "$(git branch --show-current)" = "feature"
+    test "$(cat one.txt)" = "uncommitted"
+
+    # trailers are carried over, once, by both backends
+    GPTCOMMIT__GIT__BACKEND="subprocess" gptcommit reword --yes
+    git log -1 --format=%B > "${TEMPDIR}/.git/message.txt"
+    test "$(grep -c "^Signed-off-by: Test <test@example.com>$" "${TEMPDIR}/.git/message.txt")" = 1
+    test "$(grep -c "^Change-Id: I0123456789$" "${TEMPDIR}/.git/message.txt")" = 1
+    test "$(git log -1 --format=%an HEAD~1)" = "Someone Else"
+
+    # commits already pushed to the default branch are not rewritten
+    git push -q origin HEAD~1:main
+    git fetch -q origin
+    pushed_head=$(git rev-parse HEAD)
+    if gptcommit reword --base HEAD~2 --yes 2> "${TEMPDIR}/.git/refused.txt"; then
+        exit 1
+    fi
+    grep -q "already on origin/main" "${TEMPDIR}/.git/refused.txt"
+    test "$(git rev-parse HEAD)" = "${pushed_head}"
+
+    # nor are commits already pushed to the upstream of the current branch
+    git push -q -u origin feature
+    if gptcommit reword --base origin/main --yes 2> "${TEMPDIR}/.git/refused.txt"; then
+        exit 1
+    fi
+    grep -q "already on the upstream of the current branch" "${TEMPDIR}/.git/refused.txt"
+    test "$(git rev-parse HEAD)" = "${pushed_head}"
+    git branch -q --unset-upstream
+
+    # a merge in the range is refused before the model is asked about any commit
+    git checkout -q -b side HEAD~1
+    printf 'side\n' > side.txt
+    git add side.txt
+    git commit -q -m "side"
+    git checkout -q feature
+    git merge -q --no-edit side
+    printf 'four\n' > four.txt
+    git add four.txt
+    git commit -q -m "four"
+    merged_head=$(git rev-parse HEAD)
+    for backend in gix subprocess; do
+        if GPTCOMMIT__GIT__BACKEND="${backend}" gptcommit reword --base origin/main --yes > "${TEMPDIR}/.git/merge.txt" 2>&1; then
+            exit 1
+        fi
+        if grep "foo bar" "${TEMPDIR}/.git/merge.txt" > /dev/null; then
+            exit 1
+        fi
+        grep -q "merge" "${TEMPDIR}/.git/merge.txt"
+        test "$(git rev-parse HEAD)" = "${merged_head}"
+    done
+)
+rm -rf "${TEMPDIR}" "${ORIGINDIR}"
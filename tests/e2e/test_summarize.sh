@@ -0,0 +1,86 @@
+#!/bin/sh
+set -eu
+
+DIFF_CONTENT_PATH="$(pwd)/tests/data/example_1.diff"
+
+export TEMPDIR=$(mktemp -d)
+(
+    cd "${TEMPDIR}"
+    export GPTCOMMIT__MODEL_PROVIDER="tester-foobar"
+
+    # a diff from stdin or a file works outside of a repository
+    gptcommit summarize < "${DIFF_CONTENT_PATH}" > "${TEMPDIR}/stdin.txt"
+    test "$(cat "${TEMPDIR}/stdin.txt")" = "$(printf 'foo bar\n\nfoo bar')"
+    gptcommit summarize --file - < "${DIFF_CONTENT_PATH}" > "${TEMPDIR}/dash.txt"
+    cmp "${TEMPDIR}/stdin.txt" "${TEMPDIR}/dash.txt"
+    gptcommit summarize --file "${DIFF_CONTENT_PATH}" > "${TEMPDIR}/file.txt"
+    cmp "${TEMPDIR}/stdin.txt" "${TEMPDIR}/file.txt"
+
+    # a plain `diff -u` has no `diff --git` headers, and is rejected with a hint
+    printf 'a\n' > old.txt
+    printf 'b\n' > new.txt
+    if diff -u old.txt new.txt | gptcommit summarize 2> "${TEMPDIR}/err.txt"; then
+        exit 1
+    fi
+    grep "git diff --no-index" "${TEMPDIR}/err.txt" > /dev/null
+    git diff --no-index old.txt new.txt | gptcommit summarize | grep "^foo bar$" > /dev/null
+
+    if printf '' | gptcommit summarize; then
+        exit 1
+    fi
+    if gptcommit summarize --staged --rev HEAD; then
+        exit 1
+    fi
+
+    git init -b main
+    git config user.email "test@example.com"
+    git config user.name "Test"
+    printf 'fn main() {}\n' > main.rs
+    git add main.rs
+    git commit -m "initial"
+    printf 'new\n' > added.txt
+    git add added.txt
+    git commit -m "Add a file"
+
+    if gptcommit summarize --staged; then
+        exit 1
+    fi
+    printf 'fn main() {\n    println!("hi");\n}\n' > main.rs
+    git add main.rs
+    gptcommit summarize --staged | grep "^foo bar$" > /dev/null
+    gptcommit summarize --rev HEAD | grep "^foo bar$" > /dev/null
+    gptcommit summarize --range HEAD~1..HEAD | grep "^foo bar$" > /dev/null
+
+    # in a pipeline, stdout is exactly the message even when a file is skipped
+    # with a warning
+    printf '# lock\n' > Cargo.lock
+    git add Cargo.lock
+    git diff --cached | gptcommit summarize > "${TEMPDIR}/pipe.txt" 2> "${TEMPDIR}/pipe-err.txt"
+    test "$(cat "${TEMPDIR}/pipe.txt")" = "$(printf 'foo bar\n\nfoo bar')"
+    grep "WARN" "${TEMPDIR}/pipe-err.txt" > /dev/null
+    # the warning doesn't break the JSON either
+    gptcommit summarize --staged --format json > "${TEMPDIR}/staged.json"
+    python3 -c '
+import json, sys
+output = json.load(open(sys.argv[1]))
+assert output["message"] == "foo bar\n\nfoo bar"
+assert [file["file_name"] for file in output["skipped_files"]] == ["Cargo.lock"]
+' "${TEMPDIR}/staged.json"
+    git rm -q --cached Cargo.lock
+    rm Cargo.lock
+
+    # ignored files are reported with the reason they were left out
+    printf 'src/\n' > .gptcommitignore
+    gptcommit summarize --format json < "${DIFF_CONTENT_PATH}" > "${TEMPDIR}/out.json"
+    grep -q '"schema_version": 1,' "${TEMPDIR}/out.json"
+    grep -q '"title": "foo bar",' "${TEMPDIR}/out.json"
+    grep -q '"message": "foo bar\\n\\nfoo bar",' "${TEMPDIR}/out.json"
+    grep -q '"file_name": ".vscode/launch.json",' "${TEMPDIR}/out.json"
+    grep -q '"file_name": "src/main.rs",' "${TEMPDIR}/out.json"
+    grep -q '"reason": "ignored by the file_ignore setting or .gptcommitignore"' "${TEMPDIR}/out.json"
+    grep -q '"model": "tester-foobar",' "${TEMPDIR}/out.json"
+    grep -q '"duration_ms": [0-9]*' "${TEMPDIR}/out.json"
+    # the root commit is compared with the empty tree
+    gptcommit summarize --rev HEAD~1 | grep "^foo bar$" > /dev/null
+)
+rm -rf "${TEMPDIR}"